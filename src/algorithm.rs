@@ -1,52 +1,173 @@
-use crate::arms::Arms;
-use crate::sampler::Sampler;
-use crate::structure::CombinatorialStructure;
+pub mod brute_force;
+
+use crate::arms::BanditEnvironment;
+use crate::error::Error;
+use crate::estimator::MeanEstimator;
+use crate::sampler::{ConfidenceBound, Sampler};
+use crate::structure::{check_weights, maxgap_arm, CombinatorialStructure};
+
+/// The number of samples drawn from each remaining arm in a phase by default.
+pub const DEFAULT_SAMPLES_PER_PHASE: usize = 100;
 
 /// Find the optimal superarm by the CSAR algorithm.
-pub fn csar(mut structure: impl CombinatorialStructure, arms: &mut Arms) -> Vec<usize> {
+/// Return an error if `arms` has a different number of arms from `structure`,
+/// if `structure` has no basis, or if an estimated mean is NaN.
+pub fn csar(
+    structure: impl CombinatorialStructure,
+    arms: &mut impl BanditEnvironment,
+) -> Result<Vec<usize>, Error> {
+    csar_with_estimator(structure, arms, &Sampler::new(), DEFAULT_SAMPLES_PER_PHASE)
+}
+
+/// Find the optimal superarm by the CSAR algorithm,
+/// estimating the mean of each arm by a clone of `estimator`.
+/// Each remaining arm is sampled `samples_per_phase` times in every phase.
+/// Return an error if `arms` has a different number of arms from `structure`,
+/// if `structure` has no basis, or if an estimated mean is NaN.
+pub fn csar_with_estimator<Estimator: MeanEstimator>(
+    mut structure: impl CombinatorialStructure,
+    arms: &mut impl BanditEnvironment,
+    estimator: &Estimator,
+    samples_per_phase: usize,
+) -> Result<Vec<usize>, Error> {
     let mut accepted_arms = Vec::<usize>::new();
 
     // the total number of arms
-    let n = structure.get_arms().len();
+    let n = structure.get_arm_num();
+    if arms.get_arm_num() != n {
+        return Err(Error::ArmNumMismatch {
+            expected: n,
+            actual: arms.get_arm_num(),
+        });
+    }
 
-    let mut samplers: Vec<Sampler> = (0..n).map(|_| Sampler::new()).collect();
+    let mut estimators: Vec<Estimator> = (0..n).map(|_| estimator.clone()).collect();
+    let mut weights = vec![0_f64; n];
 
-    for _ in 0..n {
-        // sample the remaining arms 100 times
+    for _ in 0..structure.get_arms().len() {
+        // sample the remaining arms `samples_per_phase` times
         for i in structure.get_arms() {
-            for _ in 0..100 {
-                samplers[i].observe(arms.sample(i));
+            for _ in 0..samples_per_phase {
+                estimators[i].observe(arms.sample(i));
             }
+            // Only the remaining arms have new estimates.
+            weights[i] = estimators[i].estimate();
         }
 
-        let weights: Vec<f64> = samplers.iter().map(|sampler| sampler.get_mean()).collect();
-
-        // Find the optimal superarm and the arm with the maximum gap.
-        let best_arms = structure.optimal(&weights).unwrap();
-        let maxgap_arm = structure.fast_maxgap(&weights);
+        // Find the arm with the maximum gap and the optimal superarm.
+        let maxgap_arm = structure.fast_maxgap(&weights)?;
+        let best_arms = structure.optimal(&weights).ok_or(Error::NoBasis)?;
 
         // Contract or delete the arm.
         if best_arms.contains(&maxgap_arm) {
             accepted_arms.push(maxgap_arm);
-            structure.contract_arm(maxgap_arm);
+            structure.contract_arm(maxgap_arm)?;
         } else {
-            structure.delete_arm(maxgap_arm);
+            structure.delete_arm(maxgap_arm)?;
         }
     }
 
-    accepted_arms
+    Ok(accepted_arms)
 }
 
-/// Find the arm with the maximum gap.
-/// It is required that the number of arms is greater than 0 and equal to the length of `weights`.
-pub fn naive_maxgap(structure: &impl CombinatorialStructure, weights: &[f64]) -> usize {
+/// Find the optimal superarm by CSAR which accepts or rejects several arms in a phase
+/// when their empirical gaps exceed the confidence widths.
+///
+/// In each phase, every remaining arm is sampled `samples_per_phase` times,
+/// and its empirical mean has the confidence radius of `bound` with probability `1 - delta / n^2`.
+/// Every arm whose empirical gap exceeds `width` times the largest radius is certified and decided at once.
+/// With probability at least `1 - delta`, every certified decision is correct on matroids
+/// if the rewards satisfy `bound`.
+/// If there is no such arm, the arm with the maximum gap is decided as in `csar`,
+/// which has no such guarantee.
+/// Return an error in the same cases as `csar_with_estimator`.
+pub fn csar_with_early_decisions(
+    mut structure: impl CombinatorialStructure,
+    arms: &mut impl BanditEnvironment,
+    samples_per_phase: usize,
+    bound: ConfidenceBound,
+    delta: f64,
+) -> Result<Vec<usize>, Error> {
+    let mut accepted_arms = Vec::<usize>::new();
+
+    let n = structure.get_arm_num();
+    if arms.get_arm_num() != n {
+        return Err(Error::ArmNumMismatch {
+            expected: n,
+            actual: arms.get_arm_num(),
+        });
+    }
+
+    // The union bound over the arms and at most n phases.
+    let delta = delta / (n * n).max(1) as f64;
+    let mut samplers = vec![Sampler::new(); n];
+    let mut weights = vec![0_f64; n];
+
+    while !structure.get_arms().is_empty() {
+        let remaining_arms = structure.get_arms();
+        let mut max_radius = 0_f64;
+        for &i in &remaining_arms {
+            for _ in 0..samples_per_phase {
+                samplers[i].observe(arms.sample(i));
+            }
+            weights[i] = samplers[i].get_mean();
+            max_radius = max_radius.max(bound.radius(&samplers[i], delta));
+        }
+
+        let gaps = structure.all_gaps(&weights)?;
+        let best_arms = structure.optimal(&weights).ok_or(Error::NoBasis)?;
+        let mut in_best = vec![false; n];
+        for &i in &best_arms {
+            in_best[i] = true;
+        }
+
+        // The arms certified by the confidence bounds, or the arm with the maximum gap.
+        let width = structure.width() as f64;
+        let mut decided_arms: Vec<usize> = remaining_arms
+            .iter()
+            .copied()
+            .filter(|&i| gaps[i] > width * max_radius)
+            .collect();
+        if decided_arms.is_empty() {
+            decided_arms.push(maxgap_arm(&remaining_arms, &gaps)?);
+        }
+
+        for i in decided_arms {
+            if in_best[i] {
+                accepted_arms.push(i);
+                structure.contract_arm(i)?;
+            } else {
+                structure.delete_arm(i)?;
+            }
+        }
+    }
+
+    Ok(accepted_arms)
+}
+
+/// Find the gap of every remaining arm by solving the optimization once per arm.
+/// The gaps are indexed by the arms, and they are NaN for the removed arms.
+/// Return an error if no arm remains, if the length of `weights` differs from the number of arms,
+/// if the weight of a remaining arm is NaN, or if there is no basis.
+pub fn naive_gaps(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+) -> Result<Vec<f64>, Error> {
     let arms = structure.get_arms();
     // Check the requirement
-    assert_ne!(arms.len(), 0);
-    assert_eq!(arms.len(), weights.len());
+    if arms.is_empty() {
+        return Err(Error::NoArm);
+    }
+    if structure.get_arm_num() != weights.len() {
+        return Err(Error::ArmNumMismatch {
+            expected: structure.get_arm_num(),
+            actual: weights.len(),
+        });
+    }
+    check_weights(&arms, weights)?;
 
     // Find the optimal superarm
-    let opt_arms = structure.optimal(weights).unwrap();
+    let opt_arms = structure.optimal(weights).ok_or(Error::NoBasis)?;
     let opt_weight: f64 = opt_arms.iter().map(|&i| weights[i]).sum();
 
     // Whether or not the arm is in the optimal superarm.
@@ -55,10 +176,7 @@ pub fn naive_maxgap(structure: &impl CombinatorialStructure, weights: &[f64]) ->
         in_opt[i] = true;
     }
 
-    let mut maxgap = f64::NEG_INFINITY;
-    let mut maxgap_arm = None;
-
-    let mut gaps = Vec::<f64>::new();
+    let mut gaps = vec![f64::NAN; structure.get_arm_num()];
 
     for &i in &arms {
         let mut new_structure = structure.clone();
@@ -66,11 +184,11 @@ pub fn naive_maxgap(structure: &impl CombinatorialStructure, weights: &[f64]) ->
 
         if in_opt[i] {
             // Exclude the arm i.
-            new_structure.delete_arm(i);
+            new_structure.delete_arm(i)?;
         } else {
             // Include the arm i.
             subopt_weight += weights[i];
-            new_structure.contract_arm(i);
+            new_structure.contract_arm(i)?;
         }
 
         // Find the optimal superarm satisfying the condition of the arm i.
@@ -80,74 +198,238 @@ pub fn naive_maxgap(structure: &impl CombinatorialStructure, weights: &[f64]) ->
             // If there is no superarm, the maximum weight is -INF.
             f64::NEG_INFINITY
         };
-        let gap = opt_weight - subopt_weight;
-        gaps.push(gap);
-
-        if gap > maxgap {
-            maxgap = gap;
-            maxgap_arm = Some(i);
-        }
+        gaps[i] = opt_weight - subopt_weight;
     }
 
-    if let Some(id) = maxgap_arm {
-        id
-    } else {
-        // If any arms should not be chosen, return the first arm.
-        *arms.first().unwrap()
-    }
+    Ok(gaps)
+}
+
+/// Find the arm with the maximum gap, where the smaller index breaks ties.
+/// Return an error in the same cases as `naive_gaps`.
+pub fn naive_maxgap(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+) -> Result<usize, Error> {
+    let gaps = naive_gaps(structure, weights)?;
+    maxgap_arm(&structure.get_arms(), &gaps)
 }
 
-#[allow(dead_code)]
-pub mod tests {
+#[cfg(test)]
+mod tests {
     use crate::{
-        algorithm::{csar, naive_maxgap},
-        arms::{Arms, Weights},
-        structure::{CombinatorialStructure, RandomSample},
+        algorithm::{csar, csar_with_early_decisions, naive_maxgap},
+        arms::{Arm, Arms, SampleCounter, Weights},
+        hardness::generator::GapProfile,
+        sampler::ConfidenceBound,
+        structure::{
+            circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid,
+            CombinatorialStructure, RandomSample,
+        },
+        util::graph::generator,
     };
-    use rand::Rng;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
-    pub fn test_maxgap<Structure>(arm_num: usize)
+    fn test_maxgap<Structure>(arm_num: usize, seed: u64)
     where
         Structure: CombinatorialStructure + RandomSample,
     {
-        let mut rng = rand::thread_rng();
-        let structure = Structure::sample(arm_num);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let structure = Structure::sample(arm_num, &mut rng);
 
         // Generate weights randomly.
         let weights: Weights = (0..arm_num).map(|_| rng.gen()).collect();
 
         // Find the edge with the maximum gap.
-        let naive_arm = naive_maxgap(&structure, &weights);
-        let faster_arm = structure.fast_maxgap(&weights);
+        let naive_arm = naive_maxgap(&structure, &weights).unwrap();
+        let faster_arm = structure.fast_maxgap(&weights).unwrap();
 
         assert!(naive_arm == faster_arm);
     }
 
-    pub fn test_csar<Structure>(arm_num: usize)
+    fn test_csar<Structure>(arm_num: usize, seed: u64)
     where
         Structure: CombinatorialStructure + RandomSample,
     {
-        let structure = Structure::sample(arm_num);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let structure = Structure::sample(arm_num, &mut rng);
 
-        let mut arms = Arms::new();
-        let mut rng = rand::thread_rng();
-        for _ in 0..arm_num {
-            arms.add_arm(rng.gen(), rng.gen());
+        // Every arm is well separated with the gap 0.4.
+        let means = GapProfile::Equal { gap: 0.4 }
+            .means(&structure, &mut rng)
+            .unwrap();
+        let mut arms = Arms::new(rng.gen());
+        for &mean in &means {
+            arms.push_arm(Arm::gaussian(mean, 0.1).unwrap());
         }
 
-        let mut csar_optimal = csar(structure.clone(), &mut arms);
+        let mut csar_optimal = csar(structure.clone(), &mut arms).unwrap();
         csar_optimal.sort_unstable();
 
-        let means: Weights = structure
-            .get_arms()
-            .iter()
-            .map(|&i| arms.get_mean(i))
-            .collect();
+        let mut true_optimal = structure.optimal(&means).unwrap();
+        true_optimal.sort_unstable();
+
+        assert_eq!(csar_optimal, true_optimal);
+    }
+
+    #[test]
+    fn test_uniform_maxgap() {
+        test_maxgap::<UniformMatroid>(100, 0);
+    }
 
+    #[test]
+    fn test_uniform_csar() {
+        test_csar::<UniformMatroid>(100, 1);
+    }
+
+    #[test]
+    fn test_circuit_maxgap() {
+        test_maxgap::<CircuitMatroid>(100, 3);
+    }
+
+    #[test]
+    fn test_circuit_csar() {
+        test_csar::<CircuitMatroid>(100, 4);
+    }
+
+    #[test]
+    fn test_early_decisions() {
+        // Every arm has the gap 0.4 on the grid graph.
+        let structure = CircuitMatroid::new(&generator::grid(5, 5));
+        let mut rng = StdRng::seed_from_u64(6);
+        let means = GapProfile::Equal { gap: 0.4 }
+            .means(&structure, &mut rng)
+            .unwrap();
+        let mut arms = Arms::new(rng.gen());
+        for &mean in &means {
+            arms.push_arm(Arm::gaussian(mean, 0.1).unwrap());
+        }
         let mut true_optimal = structure.optimal(&means).unwrap();
         true_optimal.sort_unstable();
 
-        println!("csar: {:?}", csar_optimal);
-        println!("true: {:?}", true_optimal);
+        let bound = ConfidenceBound::SubGaussian { sigma: 0.1 };
+        let mut counter = SampleCounter::new(&mut arms);
+        let mut optimal =
+            csar_with_early_decisions(structure.clone(), &mut counter, 100, bound, 0.01).unwrap();
+        optimal.sort_unstable();
+        assert_eq!(optimal, true_optimal);
+        let early_samples = counter.get_count();
+
+        let mut counter = SampleCounter::new(&mut arms);
+        csar(structure, &mut counter).unwrap();
+        // Every arm is decided in the first phase.
+        assert_eq!(early_samples, 100 * means.len() as u64);
+        assert!(early_samples * 10 < counter.get_count());
+    }
+}
+
+#[cfg(test)]
+mod property_tests {
+    use crate::{
+        algorithm::{
+            brute_force::{brute_force, enumerate_bases},
+            naive_gaps, naive_maxgap,
+        },
+        structure::{
+            circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid,
+            CombinatorialStructure,
+        },
+        util::graph::Graph,
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    /// Contract or delete arms along `history` as long as a basis remains,
+    /// leaving at least one arm.
+    fn apply_history<S: CombinatorialStructure>(structure: &mut S, history: &[(bool, usize)]) {
+        for &(contract, k) in history {
+            let arms = structure.get_arms();
+            if arms.len() <= 1 {
+                break;
+            }
+            let i = arms[k % arms.len()];
+
+            let mut next = structure.clone();
+            if contract {
+                next.contract_arm(i).unwrap();
+            } else {
+                next.delete_arm(i).unwrap();
+            }
+            if next.optimal(&vec![0.0; next.get_arm_num()]).is_some() {
+                *structure = next;
+            }
+        }
+    }
+
+    /// Check `optimal`, `naive_gaps`, `all_gaps` and `fast_maxgap` against the brute-force enumeration.
+    fn check_structure(
+        mut structure: impl CombinatorialStructure,
+        weights: &[f64],
+        history: &[(bool, usize)],
+    ) -> Result<(), TestCaseError> {
+        apply_history(&mut structure, history);
+        let Ok(expected) = brute_force(&structure, weights) else {
+            // The structure has no basis from the beginning.
+            prop_assert_eq!(structure.optimal(weights), None);
+            return Ok(());
+        };
+
+        let mut optimal = structure.optimal(weights).unwrap();
+        optimal.sort_unstable();
+        prop_assert!(enumerate_bases(&structure).unwrap().contains(&optimal));
+        let opt_weight: f64 = optimal.iter().map(|&i| weights[i]).sum();
+        prop_assert_eq!(opt_weight, expected.optimal_weight);
+
+        let gaps = naive_gaps(&structure, weights).unwrap();
+        for i in structure.get_arms() {
+            prop_assert_eq!(gaps[i], expected.gaps[i]);
+        }
+        let all_gaps = structure.all_gaps(weights).unwrap();
+        for (naive_gap, fast_gap) in gaps.iter().zip(&all_gaps) {
+            prop_assert!(naive_gap == fast_gap || naive_gap.is_nan() && fast_gap.is_nan());
+        }
+
+        // The gaps are exact with the integral weights, so the ties are broken in the same way.
+        prop_assert_eq!(
+            structure.fast_maxgap(weights),
+            naive_maxgap(&structure, weights)
+        );
+        Ok(())
+    }
+
+    /// Small integral weights, which have many ties and exact sums.
+    fn weights(arm_num: usize) -> impl Strategy<Value = Vec<f64>> {
+        vec((0..4_i32).prop_map(f64::from), arm_num)
+    }
+
+    fn history() -> impl Strategy<Value = Vec<(bool, usize)>> {
+        vec((any::<bool>(), any::<usize>()), 0..6)
+    }
+
+    proptest! {
+        #[test]
+        fn uniform_matroid_test(
+            (arm_num, rank, weights) in (1..=10_usize)
+                .prop_flat_map(|n| (Just(n), 0..=n, weights(n))),
+            history in history(),
+        ) {
+            check_structure(UniformMatroid::new(arm_num, rank), &weights, &history)?;
+        }
+
+        /// Multigraphs with self-loops, which may be disconnected.
+        #[test]
+        fn circuit_matroid_test(
+            (edges, weights) in (1..=6_usize)
+                .prop_flat_map(|vnum| vec((0..vnum, 0..vnum), 1..=10))
+                .prop_flat_map(|edges| {
+                    let edge_num = edges.len();
+                    (Just(edges), weights(edge_num))
+                }),
+            history in history(),
+        ) {
+            let mut graph = Graph::new(0);
+            for (u, v) in edges {
+                graph.add_edge(u.min(v), u.max(v));
+            }
+            check_structure(CircuitMatroid::new(&graph), &weights, &history)?;
+        }
     }
 }
@@ -0,0 +1,185 @@
+use crate::{error::Error, structure::CombinatorialStructure};
+
+/// The maximum number of arms for which the bases are enumerated.
+pub const MAX_BRUTE_FORCE_ARMS: usize = 20;
+
+/// The optimal basis and the gaps found by enumerating all bases.
+pub struct BruteForce {
+    /// The first basis with the maximum weight in the enumeration.
+    pub optimal: Vec<usize>,
+    pub optimal_weight: f64,
+    /// The gaps indexed by the arms, which are NaN for the removed arms.
+    pub gaps: Vec<f64>,
+}
+
+/// Judge whether or not `structure` has a basis.
+fn has_basis(structure: &impl CombinatorialStructure) -> bool {
+    structure
+        .optimal(&vec![0.0; structure.get_arm_num()])
+        .is_some()
+}
+
+/// Enumerate the bases containing `contracted` by contracting or deleting `arms` one by one,
+/// pruning the branches without any basis.
+fn enumerate<S: CombinatorialStructure>(
+    structure: &S,
+    arms: &[usize],
+    contracted: &mut Vec<usize>,
+    bases: &mut Vec<Vec<usize>>,
+) -> Result<(), Error> {
+    let Some((&i, rest)) = arms.split_first() else {
+        // Every arm is contracted or deleted, and only the empty basis remains.
+        bases.push(contracted.clone());
+        return Ok(());
+    };
+
+    let mut with_i = structure.clone();
+    with_i.contract_arm(i)?;
+    if has_basis(&with_i) {
+        contracted.push(i);
+        enumerate(&with_i, rest, contracted, bases)?;
+        contracted.pop();
+    }
+
+    let mut without_i = structure.clone();
+    without_i.delete_arm(i)?;
+    if has_basis(&without_i) {
+        enumerate(&without_i, rest, contracted, bases)?;
+    }
+    Ok(())
+}
+
+/// List every basis of `structure` by its contraction and deletion.
+/// Each basis is sorted in increasing order.
+/// Return an error if more than `MAX_BRUTE_FORCE_ARMS` arms remain.
+pub fn enumerate_bases(structure: &impl CombinatorialStructure) -> Result<Vec<Vec<usize>>, Error> {
+    let mut arms = structure.get_arms();
+    if arms.len() > MAX_BRUTE_FORCE_ARMS {
+        return Err(Error::TooManyArms {
+            arm_num: arms.len(),
+            max: MAX_BRUTE_FORCE_ARMS,
+        });
+    }
+    arms.sort_unstable();
+
+    let mut bases = Vec::new();
+    if has_basis(structure) {
+        enumerate(structure, &arms, &mut Vec::new(), &mut bases)?;
+    }
+    Ok(bases)
+}
+
+/// Find the optimal basis and the gap of every remaining arm by enumerating all bases.
+/// Return an error if more than `MAX_BRUTE_FORCE_ARMS` arms remain,
+/// if the length of `weights` differs from the number of arms, or if there is no basis.
+pub fn brute_force(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+) -> Result<BruteForce, Error> {
+    if structure.get_arm_num() != weights.len() {
+        return Err(Error::ArmNumMismatch {
+            expected: structure.get_arm_num(),
+            actual: weights.len(),
+        });
+    }
+
+    let bases = enumerate_bases(structure)?;
+    let basis_weights: Vec<f64> = bases
+        .iter()
+        .map(|basis| basis.iter().map(|&i| weights[i]).sum())
+        .collect();
+
+    let mut opt_index = None;
+    for (k, &weight) in basis_weights.iter().enumerate() {
+        if opt_index.is_none_or(|l: usize| weight > basis_weights[l]) {
+            opt_index = Some(k);
+        }
+    }
+    let opt_index = opt_index.ok_or(Error::NoBasis)?;
+    let optimal = bases[opt_index].clone();
+    let optimal_weight = basis_weights[opt_index];
+
+    // The gap of an arm is the loss of the best basis which disagrees with the optimal one on the arm.
+    let mut gaps = vec![f64::NAN; structure.get_arm_num()];
+    for i in structure.get_arms() {
+        let in_opt = optimal.contains(&i);
+        let subopt_weight = bases
+            .iter()
+            .zip(&basis_weights)
+            .filter(|(basis, _)| basis.contains(&i) != in_opt)
+            .map(|(_, &weight)| weight)
+            .fold(f64::NEG_INFINITY, f64::max);
+        gaps[i] = optimal_weight - subopt_weight;
+    }
+
+    Ok(BruteForce {
+        optimal,
+        optimal_weight,
+        gaps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brute_force, enumerate_bases};
+    use crate::{
+        error::Error,
+        structure::{
+            circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid,
+            CombinatorialStructure,
+        },
+        util::graph::generator,
+    };
+
+    #[test]
+    fn enumerate_bases_test() {
+        // Binomial coefficients.
+        assert_eq!(
+            enumerate_bases(&UniformMatroid::new(6, 3)).unwrap().len(),
+            20
+        );
+        assert_eq!(
+            enumerate_bases(&UniformMatroid::new(5, 0)).unwrap(),
+            vec![Vec::<usize>::new()]
+        );
+        let mut structure = UniformMatroid::new(5, 2);
+        structure.delete_arm(1).unwrap().contract_arm(3).unwrap();
+        assert_eq!(
+            enumerate_bases(&structure).unwrap(),
+            vec![vec![0], vec![2], vec![4]]
+        );
+
+        // Cayley's formula.
+        let structure = CircuitMatroid::new(&generator::complete(5));
+        assert_eq!(enumerate_bases(&structure).unwrap().len(), 125);
+
+        // No basis after a bridge is deleted.
+        let mut structure = CircuitMatroid::new(&generator::grid(1, 4));
+        structure.delete_arm(1).unwrap();
+        assert!(enumerate_bases(&structure).unwrap().is_empty());
+
+        assert_eq!(
+            enumerate_bases(&UniformMatroid::new(21, 1)).unwrap_err(),
+            Error::TooManyArms {
+                arm_num: 21,
+                max: 20
+            }
+        );
+    }
+
+    #[test]
+    fn brute_force_test() {
+        let structure = UniformMatroid::new(4, 2);
+        let result = brute_force(&structure, &[1.0, 4.0, 2.0, 2.0]).unwrap();
+        assert_eq!(result.optimal, vec![1, 2]);
+        assert_eq!(result.optimal_weight, 6.0);
+        assert_eq!(result.gaps, vec![1.0, 2.0, 0.0, 0.0]);
+
+        let mut structure = UniformMatroid::new(3, 1);
+        structure.contract_arm(0).unwrap().contract_arm(1).unwrap();
+        assert_eq!(
+            brute_force(&structure, &[0.0; 3]).err(),
+            Some(Error::NoBasis)
+        );
+    }
+}
@@ -1,47 +1,405 @@
-use rand::prelude::ThreadRng;
-use rand_distr::{Distribution, Normal};
+pub mod replay;
+
+use crate::{error::Error, sampler::ConfidenceBound};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Bernoulli, Beta, Distribution, Exp, Normal, Pareto, StudentT, Uniform};
+
+pub type Weights = Vec<f64>;
+
+/// A source of stochastic rewards which CSAR interacts with.
+pub trait BanditEnvironment {
+    /// Get the number of arms.
+    fn get_arm_num(&self) -> usize;
+
+    /// Draw a reward from the arm i.
+    fn sample(&mut self, i: usize) -> f64;
+
+    /// Get the true means of the arms if they are known.
+    /// They are used only to evaluate the result, never by the algorithm.
+    fn get_means(&self) -> Option<Weights> {
+        None
+    }
+}
+
+impl<Env: BanditEnvironment + ?Sized> BanditEnvironment for &mut Env {
+    fn get_arm_num(&self) -> usize {
+        (**self).get_arm_num()
+    }
+
+    fn sample(&mut self, i: usize) -> f64 {
+        (**self).sample(i)
+    }
+
+    fn get_means(&self) -> Option<Weights> {
+        (**self).get_means()
+    }
+}
+
+/// The environment counting the samples drawn from the wrapped one.
+pub struct SampleCounter<'a, Env: BanditEnvironment + ?Sized> {
+    env: &'a mut Env,
+    count: u64,
+}
+
+impl<'a, Env: BanditEnvironment + ?Sized> SampleCounter<'a, Env> {
+    pub fn new(env: &'a mut Env) -> Self {
+        SampleCounter { env, count: 0 }
+    }
+
+    /// Return the number of samples drawn so far.
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<'a, Env: BanditEnvironment + ?Sized> BanditEnvironment for SampleCounter<'a, Env> {
+    fn get_arm_num(&self) -> usize {
+        self.env.get_arm_num()
+    }
+
+    fn sample(&mut self, i: usize) -> f64 {
+        self.count += 1;
+        self.env.sample(i)
+    }
+
+    fn get_means(&self) -> Option<Weights> {
+        self.env.get_means()
+    }
+}
+
+/// The distribution of rewards of an arm.
+#[derive(Clone, Copy)]
+enum RewardDistr {
+    Gaussian(Normal<f64>),
+    Bernoulli(Bernoulli),
+    Beta(Beta<f64>),
+    Uniform(Uniform<f64>),
+    Exponential(Exp<f64>),
+    /// The Student's t-distribution shifted by the location.
+    StudentT(StudentT<f64>, f64),
+    Pareto(Pareto<f64>),
+}
 
 pub struct Arm {
-    distr: Normal<f64>,
-    rng: ThreadRng,
+    distr: RewardDistr,
+    mean: f64,
+}
+
+/// The error for the invalid parameters of the distribution.
+fn invalid(distribution: &'static str, reason: impl ToString) -> Error {
+    Error::InvalidDistribution {
+        distribution,
+        reason: reason.to_string(),
+    }
+}
+
+/// Return the error with `reason` unless `condition` holds.
+fn require(condition: bool, distribution: &'static str, reason: &str) -> Result<(), Error> {
+    if condition {
+        Ok(())
+    } else {
+        Err(invalid(distribution, reason))
+    }
 }
 
 impl Arm {
-    pub fn new(mean: f64, std_dev: f64) -> Self {
-        Arm {
-            distr: Normal::new(mean, std_dev).unwrap(),
-            rng: rand::thread_rng(),
-        }
+    fn from_distr(distr: RewardDistr, mean: f64) -> Self {
+        Arm { distr, mean }
+    }
+
+    /// The Gaussian arm.
+    /// Return an error if `std_dev` is negative or not finite.
+    pub fn gaussian(mean: f64, std_dev: f64) -> Result<Self, Error> {
+        require(
+            std_dev >= 0.0 && std_dev.is_finite(),
+            "Gaussian",
+            "a finite std_dev >= 0 is required",
+        )?;
+        let distr = Normal::new(mean, std_dev).map_err(|err| invalid("Gaussian", err))?;
+        Ok(Self::from_distr(RewardDistr::Gaussian(distr), mean))
+    }
+
+    /// The arm which gives 1 with probability `p` and 0 otherwise.
+    /// Return an error unless `0 <= p <= 1`.
+    pub fn bernoulli(p: f64) -> Result<Self, Error> {
+        let distr = Bernoulli::new(p).map_err(|err| invalid("Bernoulli", err))?;
+        Ok(Self::from_distr(RewardDistr::Bernoulli(distr), p))
     }
-    pub fn sample(&mut self) -> f64 {
-        self.distr.sample(&mut self.rng)
+
+    /// The arm following the beta distribution on [0, 1].
+    /// Return an error unless `alpha` and `beta` are positive.
+    pub fn beta(alpha: f64, beta: f64) -> Result<Self, Error> {
+        let distr = Beta::new(alpha, beta).map_err(|err| invalid("beta", err))?;
+        Ok(Self::from_distr(
+            RewardDistr::Beta(distr),
+            alpha / (alpha + beta),
+        ))
+    }
+
+    /// The arm following the uniform distribution on [low, high).
+    /// Return an error unless `low < high`.
+    pub fn uniform(low: f64, high: f64) -> Result<Self, Error> {
+        require(
+            low < high && (high - low).is_finite(),
+            "uniform",
+            "low < high is required",
+        )?;
+        let distr = Uniform::new(low, high);
+        Ok(Self::from_distr(
+            RewardDistr::Uniform(distr),
+            (low + high) / 2.0,
+        ))
+    }
+
+    /// The arm following the exponential distribution with the rate `lambda`.
+    /// Return an error unless `lambda` is positive.
+    pub fn exponential(lambda: f64) -> Result<Self, Error> {
+        require(lambda > 0.0, "exponential", "lambda > 0 is required")?;
+        let distr = Exp::new(lambda).map_err(|err| invalid("exponential", err))?;
+        Ok(Self::from_distr(
+            RewardDistr::Exponential(distr),
+            1.0 / lambda,
+        ))
+    }
+
+    /// The arm following the Student's t-distribution shifted by `loc`.
+    /// Return an error unless `dof > 1` so that the mean exists.
+    pub fn student_t(loc: f64, dof: f64) -> Result<Self, Error> {
+        require(dof > 1.0, "Student-t", "dof > 1 is required")?;
+        let distr = StudentT::new(dof).map_err(|err| invalid("Student-t", err))?;
+        Ok(Self::from_distr(RewardDistr::StudentT(distr, loc), loc))
+    }
+
+    /// The arm following the Pareto distribution.
+    /// Return an error unless `scale > 0` and `shape > 1` so that the mean exists.
+    pub fn pareto(scale: f64, shape: f64) -> Result<Self, Error> {
+        require(shape > 1.0, "Pareto", "shape > 1 is required")?;
+        let distr = Pareto::new(scale, shape).map_err(|err| invalid("Pareto", err))?;
+        Ok(Self::from_distr(
+            RewardDistr::Pareto(distr),
+            scale * shape / (shape - 1.0),
+        ))
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> f64 {
+        match &self.distr {
+            RewardDistr::Gaussian(d) => d.sample(rng),
+            RewardDistr::Bernoulli(d) => {
+                if d.sample(rng) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            RewardDistr::Beta(d) => d.sample(rng),
+            RewardDistr::Uniform(d) => d.sample(rng),
+            RewardDistr::Exponential(d) => d.sample(rng),
+            RewardDistr::StudentT(d, loc) => loc + d.sample(rng),
+            RewardDistr::Pareto(d) => d.sample(rng),
+        }
     }
 
     pub fn get_mean(&self) -> f64 {
-        self.distr.mean()
+        self.mean
+    }
+}
+
+/// The family of reward distributions from which arms are randomly generated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum RewardFamily {
+    Gaussian,
+    Bernoulli,
+    Beta,
+    Uniform,
+    Exponential,
+    StudentT,
+    Pareto,
+}
+
+impl RewardFamily {
+    pub const ALL: [RewardFamily; 7] = [
+        RewardFamily::Gaussian,
+        RewardFamily::Bernoulli,
+        RewardFamily::Beta,
+        RewardFamily::Uniform,
+        RewardFamily::Exponential,
+        RewardFamily::StudentT,
+        RewardFamily::Pareto,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RewardFamily::Gaussian => "Gaussian",
+            RewardFamily::Bernoulli => "Bernoulli",
+            RewardFamily::Beta => "Beta",
+            RewardFamily::Uniform => "Uniform",
+            RewardFamily::Exponential => "Exponential",
+            RewardFamily::StudentT => "Student-t",
+            RewardFamily::Pareto => "Pareto",
+        }
+    }
+
+    /// Return the confidence bound of the empirical means which holds for every arm of this family,
+    /// or `None` for the heavy-tailed families.
+    /// The bounded arms lie in intervals of length at most 1,
+    /// and the standard deviations of the Gaussian arms are less than 1.
+    pub fn confidence_bound(&self) -> Option<ConfidenceBound> {
+        match self {
+            RewardFamily::Gaussian => Some(ConfidenceBound::SubGaussian { sigma: 1.0 }),
+            RewardFamily::Bernoulli | RewardFamily::Beta | RewardFamily::Uniform => {
+                Some(ConfidenceBound::Hoeffding { range: 1.0 })
+            }
+            RewardFamily::Exponential | RewardFamily::StudentT | RewardFamily::Pareto => None,
+        }
+    }
+
+    /// Randomly generate an arm of this family.
+    /// The mean is in [0, 1) except for the heavy-tailed Pareto arms.
+    pub fn sample_arm(&self, rng: &mut impl Rng) -> Arm {
+        let arm = match self {
+            RewardFamily::Gaussian => Arm::gaussian(rng.gen(), rng.gen()),
+            RewardFamily::Bernoulli => Arm::bernoulli(rng.gen()),
+            RewardFamily::Beta => Arm::beta(rng.gen_range(0.5..5.0), rng.gen_range(0.5..5.0)),
+            RewardFamily::Uniform => {
+                let low: f64 = rng.gen();
+                Arm::uniform(low, low + rng.gen_range(f64::EPSILON..1.0))
+            }
+            RewardFamily::Exponential => Arm::exponential(1.0 / rng.gen_range(0.05..1.0)),
+            RewardFamily::StudentT => Arm::student_t(rng.gen(), rng.gen_range(1.5..5.0)),
+            RewardFamily::Pareto => Arm::pareto(rng.gen_range(0.05..0.5), rng.gen_range(1.5..3.0)),
+        };
+        // The parameters are always in the valid ranges.
+        arm.unwrap()
+    }
+
+    /// Randomly generate an arm of this family with the mean `mean`,
+    /// where the other parameters are chosen as in `sample_arm`.
+    /// Return an error if no arm of this family has the mean,
+    /// e.g. unless `0 < mean < 1` for the beta arms or `mean > 0` for the exponential arms.
+    pub fn arm_with_mean(&self, mean: f64, rng: &mut impl Rng) -> Result<Arm, Error> {
+        match self {
+            RewardFamily::Gaussian => Arm::gaussian(mean, rng.gen()),
+            RewardFamily::Bernoulli => Arm::bernoulli(mean),
+            RewardFamily::Beta => {
+                let concentration = rng.gen_range(1.0..10.0);
+                Arm::beta(mean * concentration, (1.0 - mean) * concentration)
+            }
+            RewardFamily::Uniform => {
+                let half_width = rng.gen_range(f64::EPSILON..0.5);
+                Arm::uniform(mean - half_width, mean + half_width)
+            }
+            RewardFamily::Exponential => {
+                require(mean > 0.0, "exponential", "mean > 0 is required")?;
+                Arm::exponential(1.0 / mean)
+            }
+            RewardFamily::StudentT => Arm::student_t(mean, rng.gen_range(1.5..5.0)),
+            RewardFamily::Pareto => {
+                let shape = rng.gen_range(1.5..3.0);
+                Arm::pareto(mean * (shape - 1.0) / shape, shape)
+            }
+        }
     }
 }
 
 pub struct Arms {
     arms: Vec<Arm>,
+    rng: StdRng,
 }
 
 impl Arms {
-    pub fn new() -> Self {
-        Arms { arms: Vec::new() }
+    /// Rewards are drawn by the RNG seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Arms {
+            arms: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
     }
 
-    pub fn add_arm(&mut self, mean: f64, std_dev: f64) {
-        self.arms.push(Arm::new(mean, std_dev))
+    pub fn push_arm(&mut self, arm: Arm) {
+        self.arms.push(arm)
     }
+}
 
-    pub fn sample(&mut self, i: usize) -> f64 {
-        self.arms[i].sample()
+impl BanditEnvironment for Arms {
+    fn get_arm_num(&self) -> usize {
+        self.arms.len()
     }
 
-    pub fn get_mean(&mut self, i: usize) -> f64 {
-        self.arms[i].get_mean()
+    fn sample(&mut self, i: usize) -> f64 {
+        self.arms[i].sample(&mut self.rng)
+    }
+
+    fn get_means(&self) -> Option<Weights> {
+        Some(self.arms.iter().map(|arm| arm.get_mean()).collect())
     }
 }
 
-pub type Weights = Vec<f64>;
+#[cfg(test)]
+mod tests {
+    use super::{Arm, Error, RewardFamily};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn empirical_means_test() {
+        let arms = vec![
+            Arm::gaussian(0.3, 0.5),
+            Arm::bernoulli(0.7),
+            Arm::beta(2.0, 3.0),
+            Arm::uniform(0.2, 0.6),
+            Arm::exponential(4.0),
+            Arm::student_t(0.5, 5.0),
+            Arm::pareto(1.0, 4.0),
+        ];
+        let arms: Vec<Arm> = arms.into_iter().map(Result::unwrap).collect();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample_num = 200_000;
+        for arm in arms {
+            let empirical_mean =
+                (0..sample_num).map(|_| arm.sample(&mut rng)).sum::<f64>() / sample_num as f64;
+            assert!(
+                (empirical_mean - arm.get_mean()).abs() < 0.02,
+                "The empirical mean {} is far from the true mean {}.",
+                empirical_mean,
+                arm.get_mean()
+            );
+        }
+    }
+
+    #[test]
+    fn arm_with_mean_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for family in RewardFamily::ALL {
+            for mean in [0.05, 0.5, 0.95] {
+                let arm = family.arm_with_mean(mean, &mut rng).unwrap();
+                assert!((arm.get_mean() - mean).abs() < 1e-12, "{}", family.name());
+            }
+        }
+        assert!(RewardFamily::Bernoulli
+            .arm_with_mean(1.5, &mut rng)
+            .is_err());
+        assert!(RewardFamily::Beta.arm_with_mean(1.0, &mut rng).is_err());
+        assert!(RewardFamily::Exponential
+            .arm_with_mean(0.0, &mut rng)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_arms_test() {
+        assert!(Arm::gaussian(0.0, -1.0).is_err());
+        assert!(Arm::bernoulli(1.5).is_err());
+        assert!(Arm::beta(0.0, 1.0).is_err());
+        assert!(Arm::uniform(1.0, 1.0).is_err());
+        assert!(Arm::exponential(0.0).is_err());
+        assert!(Arm::student_t(0.0, 1.0).is_err());
+        assert!(Arm::pareto(1.0, 1.0).is_err());
+        assert!(matches!(
+            Arm::pareto(-1.0, 2.0),
+            Err(Error::InvalidDistribution {
+                distribution: "Pareto",
+                ..
+            })
+        ));
+    }
+}
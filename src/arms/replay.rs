@@ -0,0 +1,165 @@
+use super::{BanditEnvironment, Weights};
+use crate::error::Error;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// How the recorded rewards are served.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum ReplayMode {
+    /// Serve the records in the recorded order, and start over after the last one.
+    Sequential,
+    /// Draw a record uniformly at random with replacement.
+    Bootstrap,
+}
+
+/// Arms replaying recorded reward logs.
+#[derive(Clone)]
+pub struct ReplayArms {
+    records: Vec<Vec<f64>>,
+    cursors: Vec<usize>,
+    mode: ReplayMode,
+    rng: StdRng,
+}
+
+impl ReplayArms {
+    /// Return an error if an arm has no record.
+    /// The RNG for the bootstrap is seeded with 0 until `restart` is called.
+    pub fn new(records: Vec<Vec<f64>>, mode: ReplayMode) -> Result<Self, Error> {
+        if let Some(arm) = records.iter().position(|r| r.is_empty()) {
+            return Err(Error::NoRecord { arm });
+        }
+        Ok(ReplayArms {
+            cursors: vec![0; records.len()],
+            records,
+            mode,
+            rng: StdRng::seed_from_u64(0),
+        })
+    }
+
+    /// Parse reward logs in plain text.
+    ///
+    /// * Each line holds the rewards of one arm, separated by whitespaces or commas.
+    /// * Empty lines and lines starting with `#` are ignored.
+    /// * Every reward must be finite.
+    pub fn parse(text: &str, mode: ReplayMode) -> io::Result<Self> {
+        let mut records = Vec::new();
+
+        for (line_i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let rewards = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|token| !token.is_empty())
+                .map(|token| {
+                    token
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|reward| reward.is_finite())
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                ErrorKind::InvalidData,
+                                format!("Invalid reward {:?} at line {}.", token, line_i + 1),
+                            )
+                        })
+                })
+                .collect::<io::Result<Vec<f64>>>()?;
+            records.push(rewards);
+        }
+
+        if records.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "No arm is recorded.",
+            ));
+        }
+
+        Ok(ReplayArms::new(records, mode)?)
+    }
+
+    /// Load reward logs from a plain text file in the format of `parse`.
+    pub fn from_file(path: impl AsRef<Path>, mode: ReplayMode) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?, mode)
+    }
+
+    /// Rewind every arm to its first record and reseed the RNG with `seed`.
+    pub fn restart(&mut self, seed: u64) -> &mut Self {
+        self.cursors.iter_mut().for_each(|c| *c = 0);
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl BanditEnvironment for ReplayArms {
+    fn get_arm_num(&self) -> usize {
+        self.records.len()
+    }
+
+    fn sample(&mut self, i: usize) -> f64 {
+        let records = &self.records[i];
+        match self.mode {
+            ReplayMode::Sequential => {
+                let reward = records[self.cursors[i]];
+                self.cursors[i] = (self.cursors[i] + 1) % records.len();
+                reward
+            }
+            ReplayMode::Bootstrap => records[self.rng.gen_range(0..records.len())],
+        }
+    }
+
+    /// The empirical means over the full records.
+    fn get_means(&self) -> Option<Weights> {
+        Some(
+            self.records
+                .iter()
+                .map(|r| r.iter().sum::<f64>() / r.len() as f64)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplayArms, ReplayMode};
+    use crate::arms::BanditEnvironment;
+
+    #[test]
+    fn replay_test() {
+        let text = "# arm logs\n1 2 3\n\n0.5,1.5\n";
+
+        let mut arms = ReplayArms::parse(text, ReplayMode::Sequential).unwrap();
+        assert_eq!(arms.get_arm_num(), 2);
+        assert_eq!(arms.get_means(), Some(vec![2.0, 1.0]));
+
+        let replayed: Vec<f64> = (0..4).map(|_| arms.sample(0)).collect();
+        assert_eq!(replayed, vec![1.0, 2.0, 3.0, 1.0]);
+        arms.restart(0);
+        assert_eq!(arms.sample(0), 1.0);
+
+        let mut arms = ReplayArms::parse(text, ReplayMode::Bootstrap).unwrap();
+        let resampled: Vec<f64> = (0..100).map(|_| arms.sample(1)).collect();
+        assert!(resampled.iter().all(|r| [0.5, 1.5].contains(r)));
+        arms.restart(0);
+        assert_eq!(
+            (0..100).map(|_| arms.sample(1)).collect::<Vec<f64>>(),
+            resampled
+        );
+
+        assert!(ReplayArms::parse("1 x", ReplayMode::Sequential).is_err());
+        assert!(ReplayArms::parse("# empty", ReplayMode::Sequential).is_err());
+        assert!(ReplayArms::parse("1 2\n,\n", ReplayMode::Sequential).is_err());
+        for text in ["1 nan", "inf 2", "1,-inf"] {
+            assert!(matches!(
+                ReplayArms::parse(text, ReplayMode::Sequential),
+                Err(err) if err.kind() == std::io::ErrorKind::InvalidData
+            ));
+        }
+    }
+}
@@ -0,0 +1,643 @@
+use std::path::PathBuf;
+
+use crate::experiment::{
+    registry::{find_structure, StructureParams, STRUCTURES},
+    AlgorithmType, BenchSettings, DumpTarget, EnumMeanEstimators, OutputFormat, Settings,
+    SourceSetting,
+};
+use csar::{
+    algorithm::DEFAULT_SAMPLES_PER_PHASE,
+    arms::{
+        replay::{ReplayArms, ReplayMode},
+        BanditEnvironment, RewardFamily,
+    },
+    hardness::generator::GapProfile,
+    util::graph::reader::GraphFormat,
+};
+
+const USAGE: &str = r"Usage: csar [COMMAND] [OPTIONS]
+       csar sweep <CONFIG> [--output <DIR>]
+
+Without a command, the settings are asked interactively.
+
+Commands:
+    run       Run CSAR repeatedly and report how often it finds the optimal superarm
+    bench     Measure the elapsed time of naive_maxgap, fast_maxgap, reachability_graph and CSAR
+              over the numbers of arms
+    verify    Check that fast_maxgap agrees with naive_maxgap,
+              and that small instances agree with the enumeration of all bases
+    dump      Write a graph of an instance in the Graphviz DOT language into stdout,
+              highlighting the optimal basis for random weights and annotating the gaps
+    sweep     Run every experiment in the TOML or JSON config file,
+              writing the results of each into the directory [default: results]
+    help      Print this message
+
+Options:
+    --structure <NAME>                     The combinatorial structure [default: circuit]
+    --params <KEY=VALUE,...>               The parameters of the structure [default: random]
+    --graph <PATH>                         Use the circuit matroid of the graph file as the circuit structure,
+                                           with the rewards of the edges if the file gives them
+    --graph-format <NAME>                  The format of the graph file [default: by the extension]
+                                           (edgelist, dimacs, metis, mtx)
+    --arms <N>                             The number of arms [default: 100]
+    --trials <N>                           The number of trials [default: 1]
+    --seed <SEED>                          The seed of the whole experiment [default: random]
+    --budget <N>                           Samples per remaining arm in a phase [default: 100]
+    --distribution <NAME>                  The reward distribution of synthetic arms [default: gaussian]
+                                           (gaussian, bernoulli, beta, uniform, exponential, student-t, pareto)
+    --gaps <PROFILE>                       The gaps targeted by the means of synthetic arms [default: random]
+                                           (equal:<GAP>, polynomial:<GAP>:<EXPONENT>,
+                                           geometric:<GAP>:<RATIO>, near-tie:<GAP>:<TIE>) with GAP <= 1/2,
+                                           exact for the arms exchangeable with the pair of the smallest
+                                           target, which is every arm of a uniform matroid
+    --replay <PATH>                        Replay the reward logs instead of synthetic arms
+    --replay-mode <sequential|bootstrap>   How the reward logs are replayed [default: sequential]
+    --algorithm <csar|early>               The variant of CSAR [default: csar]
+                                           `early` decides several arms in a phase by the confidence bound
+                                           of the Gaussian, Bernoulli, beta or uniform arms
+    --estimator <NAME|all>                 The mean estimator used by CSAR [default: empirical]
+                                           (empirical, median-of-means, catoni, truncated)
+    --format <text|csv|json>               The output format of `run` and `bench` [default: text]
+    --threads <N>                          The number of threads running trials [default: all cores]
+    --target <input|reachability>          The graph written by `dump` [default: reachability]
+
+Options of bench:
+    --structure <NAME,...|all>             The structures to compare, which cannot be combined with --graph
+                                           [default: circuit]
+    --estimator <NAME>                     The single mean estimator used by CSAR [default: empirical]
+    --sizes <N,...|MIN..MAX>               The numbers of arms, where MIN..MAX takes 1, 2 and 5
+                                           times the powers of 10 between them [default: --arms]
+    --naive-limit <N>                      The maximum number of arms for naive_maxgap [default: 10000]
+    --csar-limit <N>                       The maximum number of arms for CSAR [default: 1000]";
+
+/// The usage followed by the list of the registered structures.
+pub fn usage() -> String {
+    let mut text = format!("{}\n\nStructures:", USAGE);
+    for factory in STRUCTURES {
+        text += &format!("\n    {:<39}{}", factory.key, factory.name);
+        for (key, description) in factory.params {
+            text += &format!("\n        {:<35}{}", format!("{}=<N>", key), description);
+        }
+    }
+    text
+}
+
+pub enum Command {
+    /// Ask the settings on stdin, with the seed if given.
+    Interactive(Option<u64>),
+    Run(Settings),
+    Bench(Settings, BenchSettings),
+    Verify(Settings),
+    Dump(Settings, DumpTarget),
+    /// Run every experiment in the config file, and write the results into the directory.
+    Sweep(PathBuf, PathBuf),
+    Help,
+}
+
+/// Parse the command-line arguments except the program name.
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let (command, options) = match args.first().map(String::as_str) {
+        None => return Ok(Command::Interactive(None)),
+        Some("help" | "--help" | "-h") => return Ok(Command::Help),
+        Some(command @ ("run" | "bench" | "verify" | "dump")) => (command, &args[1..]),
+        Some("sweep") => {
+            let config = args.get(1).ok_or("sweep requires a config file.")?;
+            let mut output = PathBuf::from("results");
+            for (key, value) in parse_options(&args[2..])? {
+                match key.as_str() {
+                    "output" => output = PathBuf::from(value),
+                    _ => return Err(format!("Unknown option \"--{}\".", key)),
+                }
+            }
+            return Ok(Command::Sweep(PathBuf::from(config), output));
+        }
+        // Only the seed can be given in the interactive mode.
+        Some("--seed") if args.len() == 2 => {
+            return Ok(Command::Interactive(Some(parse_value("seed", &args[1])?)));
+        }
+        Some(arg) => return Err(format!("Unknown command {:?}.", arg)),
+    };
+
+    let mut options = parse_options(options)?;
+    let mut target = DumpTarget::Reachability;
+    if command == "dump" {
+        if let Some(pos) = options.iter().position(|(key, _)| key == "target") {
+            target = match options.remove(pos).1.as_str() {
+                "input" => DumpTarget::Input,
+                "reachability" => DumpTarget::Reachability,
+                value => return Err(format!("Unknown target {:?}.", value)),
+            };
+        }
+    }
+
+    let mut bench = BenchSettings::default();
+    if command == "bench" {
+        let mut rest = Vec::new();
+        for (key, value) in options {
+            match key.as_str() {
+                "sizes" => bench.sizes = parse_sizes(&value)?,
+                "naive-limit" => bench.naive_limit = parse_value(&key, &value)?,
+                "csar-limit" => bench.csar_limit = parse_value(&key, &value)?,
+                // Several structures can be compared at once.
+                "structure" if value == "all" => bench.structures = STRUCTURES.iter().collect(),
+                "structure" if value.contains(',') => {
+                    bench.structures = value
+                        .split(',')
+                        .map(|key| {
+                            find_structure(key.trim())
+                                .ok_or_else(|| format!("Unknown structure {:?}.", key))
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                _ => rest.push((key, value)),
+            }
+        }
+        options = rest;
+    }
+
+    let settings = parse_settings(&options)?;
+    Ok(match command {
+        "run" => Command::Run(settings),
+        "bench" => Command::Bench(settings, bench),
+        "dump" => Command::Dump(settings, target),
+        _ => Command::Verify(settings),
+    })
+}
+
+/// Split `--key value` pairs.
+fn parse_options(args: &[String]) -> Result<Vec<(String, String)>, String> {
+    let mut options = Vec::new();
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        let key = option
+            .strip_prefix("--")
+            .ok_or_else(|| format!("Unexpected argument {:?}.", option))?;
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} requires a value.", option))?;
+        options.push((key.to_string(), value.clone()));
+    }
+    Ok(options)
+}
+
+/// Build the settings of an experiment from pairs of an option name without `--` and its value.
+/// The options not given take the default values.
+pub fn parse_settings(options: &[(String, String)]) -> Result<Settings, String> {
+    let mut settings = Settings {
+        structure: find_structure("circuit").unwrap(),
+        structure_params: StructureParams::default(),
+        graph: None,
+        source: SourceSetting::Synthetic(RewardFamily::Gaussian),
+        arm_num: 100,
+        gap_profile: None,
+        algorithm: AlgorithmType::Csar,
+        estimators: vec![EnumMeanEstimators::all().swap_remove(0)],
+        samples_per_phase: DEFAULT_SAMPLES_PER_PHASE,
+        trials: 1,
+        seed: rand::random(),
+        format: OutputFormat::Text,
+        threads: default_threads(),
+    };
+    let mut family = RewardFamily::Gaussian;
+    let mut replay_path = None;
+    let mut replay_mode = ReplayMode::Sequential;
+    let mut graph_path = None;
+    let mut graph_format = None;
+
+    for (key, value) in options {
+        match key.as_str() {
+            "structure" => {
+                settings.structure = find_structure(value)
+                    .ok_or_else(|| format!("Unknown structure {:?}.", value))?;
+            }
+            "params" => settings.structure_params = StructureParams::parse(value)?,
+            "graph" => graph_path = Some(PathBuf::from(value)),
+            "graph-format" => {
+                graph_format = Some(
+                    *GraphFormat::ALL
+                        .iter()
+                        .find(|f| f.key() == value)
+                        .ok_or_else(|| format!("Unknown graph format {:?}.", value))?,
+                );
+            }
+            "arms" => settings.arm_num = parse_positive(key, value)?,
+            "gaps" => settings.gap_profile = Some(parse_gap_profile(value)?),
+            "trials" => settings.trials = parse_positive(key, value)?,
+            "seed" => settings.seed = parse_value(key, value)?,
+            "budget" => settings.samples_per_phase = parse_positive(key, value)?,
+            "threads" => settings.threads = parse_positive(key, value)?,
+            "distribution" => {
+                family = *RewardFamily::ALL
+                    .iter()
+                    .find(|f| f.name().eq_ignore_ascii_case(value))
+                    .ok_or_else(|| format!("Unknown distribution {:?}.", value))?;
+            }
+            "replay" => replay_path = Some(PathBuf::from(value)),
+            "replay-mode" => {
+                replay_mode = match value.as_str() {
+                    "sequential" => ReplayMode::Sequential,
+                    "bootstrap" => ReplayMode::Bootstrap,
+                    _ => return Err(format!("Unknown replay mode {:?}.", value)),
+                }
+            }
+            "algorithm" => {
+                settings.algorithm = *AlgorithmType::ALL
+                    .iter()
+                    .find(|a| a.key() == value)
+                    .ok_or_else(|| format!("Unknown algorithm {:?}.", value))?;
+            }
+            "estimator" => {
+                let mut estimators = EnumMeanEstimators::all();
+                if value != "all" {
+                    estimators.retain(|e| e.key() == value);
+                    if estimators.is_empty() {
+                        return Err(format!("Unknown estimator {:?}.", value));
+                    }
+                }
+                settings.estimators = estimators;
+            }
+            "format" => {
+                settings.format = match value.as_str() {
+                    "text" => OutputFormat::Text,
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    _ => return Err(format!("Unknown format {:?}.", value)),
+                }
+            }
+            _ => return Err(format!("Unknown option \"--{}\".", key)),
+        }
+    }
+
+    settings.source = match replay_path {
+        Some(path) => SourceSetting::Replay(path, replay_mode),
+        None => SourceSetting::Synthetic(family),
+    };
+    if let Some(path) = graph_path {
+        // Only the circuit matroid is built from the graph file.
+        if settings.structure.key != "circuit" {
+            return Err(format!(
+                "--graph builds a circuit matroid, which conflicts with --structure {}.",
+                settings.structure.key
+            ));
+        }
+        let format = graph_format.unwrap_or_else(|| GraphFormat::from_path(&path));
+        settings.graph = Some((path, format));
+    }
+
+    Ok(settings)
+}
+
+/// Parse the numbers of arms separated by commas,
+/// or `<MIN>..<MAX>` for the numbers of the form 1, 2 or 5 times a power of 10 between them.
+fn parse_sizes(value: &str) -> Result<Vec<usize>, String> {
+    let sizes = match value.split_once("..") {
+        Some((min, max)) => {
+            let min = parse_positive("sizes", min.trim())?;
+            let max = parse_positive("sizes", max.trim())?;
+            let mut sizes = Vec::new();
+            let mut scale = 1_usize;
+            while scale <= max {
+                for size in [scale, 2 * scale, 5 * scale] {
+                    if min <= size && size <= max {
+                        sizes.push(size);
+                    }
+                }
+                scale = match scale.checked_mul(10) {
+                    Some(scale) => scale,
+                    None => break,
+                };
+            }
+            sizes
+        }
+        None => value
+            .split(',')
+            .map(|size| parse_positive("sizes", size.trim()))
+            .collect::<Result<_, _>>()?,
+    };
+    if sizes.is_empty() {
+        return Err(format!("No number of arms in {:?}.", value));
+    }
+    Ok(sizes)
+}
+
+/// Parse a gap profile of the form `<KIND>:<GAP>[:<PARAM>]`.
+fn parse_gap_profile(value: &str) -> Result<GapProfile, String> {
+    let mut tokens = value.split(':');
+    let kind = tokens.next().unwrap_or_default();
+    let params = tokens
+        .map(|token| parse_value::<f64>("gaps", token))
+        .collect::<Result<Vec<f64>, String>>()?;
+    match (kind, params.as_slice()) {
+        ("equal", &[gap]) => Ok(GapProfile::Equal { gap }),
+        ("polynomial", &[gap, exponent]) => Ok(GapProfile::Polynomial { gap, exponent }),
+        ("geometric", &[gap, ratio]) => Ok(GapProfile::Geometric { gap, ratio }),
+        ("near-tie", &[gap, tie]) => Ok(GapProfile::NearTie { gap, tie }),
+        _ => Err(format!("Unknown gap profile {:?}.", value)),
+    }
+}
+
+/// Use every available CPU core by default.
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {:?} for --{}.", value, key))
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<usize, String> {
+    match parse_value(key, value)? {
+        0 => Err(format!("--{} must be positive.", key)),
+        num => Ok(num),
+    }
+}
+
+fn read_int(maximum: usize, request_msg: &str) -> usize {
+    loop {
+        eprint!("[QUERY] {}", request_msg);
+
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_line(&mut buf)
+            .expect("[ERROR] Input error. Aborting.");
+
+        if let Ok(num) = buf.trim().parse() {
+            if 0 < num && num <= maximum {
+                return num;
+            }
+        }
+        eprintln!("[ERROR] Invalid input. Try again.");
+    }
+}
+
+/// Read the path to reward logs which can be loaded, and return it with the number of arms.
+fn read_replay_path(mode: ReplayMode) -> (PathBuf, usize) {
+    loop {
+        eprint!("[QUERY] The path to the reward logs > ");
+
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_line(&mut buf)
+            .expect("[ERROR] Input error. Aborting.");
+
+        let path = PathBuf::from(buf.trim());
+        match ReplayArms::from_file(&path, mode) {
+            Ok(arms) => return (path, arms.get_arm_num()),
+            Err(err) => eprintln!("[ERROR] {} Try again.", err),
+        }
+    }
+}
+
+/// Ask the settings of the experiment on stdin.
+pub fn query_settings(seed: Option<u64>) -> Settings {
+    // Every random choice in the experiment derives from this seed.
+    let seed = seed.unwrap_or_else(rand::random);
+
+    let structure_menu: String = STRUCTURES
+        .iter()
+        .enumerate()
+        .map(|(i, factory)| format!("    {}. {}\n", i + 1, factory.name))
+        .collect();
+    let structure = &STRUCTURES[read_int(
+        STRUCTURES.len(),
+        &format!(
+            "Which combinatorial structure to test?\n{}> ",
+            structure_menu
+        ),
+    ) - 1];
+    eprintln!("[INFO] {} is chosen.", structure.name);
+
+    let source = if read_int(
+        2,
+        r"Where do the rewards come from?
+    1. Synthetic arms
+    2. Recorded reward logs
+> ",
+    ) == 1
+    {
+        let family_menu: String = RewardFamily::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, family)| format!("    {}. {}\n", i + 1, family.name()))
+            .collect();
+        let family = RewardFamily::ALL[read_int(
+            RewardFamily::ALL.len(),
+            &format!(
+                "Which reward distribution do the arms follow?\n{}> ",
+                family_menu
+            ),
+        ) - 1];
+        eprintln!("[INFO] {} rewards are chosen.", family.name());
+        SourceSetting::Synthetic(family)
+    } else {
+        let mode = [ReplayMode::Sequential, ReplayMode::Bootstrap][read_int(
+            2,
+            r"How are the records replayed?
+    1. Sequentially
+    2. Bootstrap (resampling with replacement)
+> ",
+        ) - 1];
+        let (path, arm_num) = read_replay_path(mode);
+        eprintln!("[INFO] {} arm(s).", arm_num);
+        SourceSetting::Replay(path, mode)
+    };
+
+    let arm_num = match &source {
+        SourceSetting::Synthetic(_) => {
+            let arm_num = read_int(100_000, "The number of arms (up to 100,000) > ");
+            eprintln!("[INFO] {} arm(s).", arm_num);
+            arm_num
+        }
+        SourceSetting::Replay(_, _) => 0,
+    };
+
+    // The early decisions require the confidence bound of the reward distribution.
+    let algorithm = match &source {
+        SourceSetting::Synthetic(family) if family.confidence_bound().is_some() => {
+            let algorithm_menu: String = AlgorithmType::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, algorithm)| format!("    {}. {}\n", i + 1, algorithm.name()))
+                .collect();
+            AlgorithmType::ALL[read_int(
+                AlgorithmType::ALL.len(),
+                &format!("Which algorithm to run?\n{}> ", algorithm_menu),
+            ) - 1]
+        }
+        _ => AlgorithmType::Csar,
+    };
+    eprintln!("[INFO] {} is chosen.", algorithm.name());
+
+    let mut estimators = EnumMeanEstimators::all();
+    let estimator_menu: String = estimators
+        .iter()
+        .enumerate()
+        .map(|(i, estimator)| format!("    {}. {}\n", i + 1, estimator.name()))
+        .collect();
+    let estimator_type = match algorithm {
+        AlgorithmType::Csar => read_int(
+            estimators.len() + 1,
+            &format!(
+                "Which mean estimator does CSAR use?\n{}    {}. Compare all of them\n> ",
+                estimator_menu,
+                estimators.len() + 1
+            ),
+        ),
+        // The early decisions use the empirical mean.
+        AlgorithmType::EarlyDecision => 1,
+    };
+    if estimator_type <= estimators.len() {
+        estimators = vec![estimators.swap_remove(estimator_type - 1)];
+    }
+    for estimator in &estimators {
+        eprintln!("[INFO] {} is chosen.", estimator.name());
+    }
+
+    let trials = read_int(100_000, "The number of trials > ");
+    eprintln!("[INFO] {} trials(s).", trials);
+
+    Settings {
+        structure,
+        structure_params: StructureParams::default(),
+        graph: None,
+        source,
+        arm_num,
+        gap_profile: None,
+        algorithm,
+        estimators,
+        samples_per_phase: DEFAULT_SAMPLES_PER_PHASE,
+        trials,
+        seed,
+        format: OutputFormat::Text,
+        threads: default_threads(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Command};
+    use crate::experiment::{
+        registry::{StructureParams, STRUCTURES},
+        AlgorithmType, DumpTarget, OutputFormat, SourceSetting,
+    };
+    use csar::{
+        arms::RewardFamily, hardness::generator::GapProfile, util::graph::reader::GraphFormat,
+    };
+
+    fn parse(args: &str) -> Result<Command, String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn parse_args_test() {
+        assert!(matches!(parse(""), Ok(Command::Interactive(None))));
+        assert!(matches!(
+            parse("--seed 3"),
+            Ok(Command::Interactive(Some(3)))
+        ));
+        assert!(matches!(parse("help"), Ok(Command::Help)));
+
+        let args =
+            "run --structure uniform --params rank=4 --arms 30 --trials 5 --seed 7 --budget 10 \
+                    --distribution student-t --estimator all --format csv --threads 2";
+        match parse(args) {
+            Ok(Command::Run(settings)) => {
+                assert_eq!(settings.structure.key, "uniform");
+                assert_eq!(
+                    settings.structure_params,
+                    StructureParams::parse("rank=4").unwrap()
+                );
+                assert_eq!(settings.arm_num, 30);
+                assert_eq!(settings.trials, 5);
+                assert_eq!(settings.seed, 7);
+                assert_eq!(settings.samples_per_phase, 10);
+                assert!(matches!(
+                    settings.source,
+                    SourceSetting::Synthetic(RewardFamily::StudentT)
+                ));
+                assert_eq!(settings.algorithm, AlgorithmType::Csar);
+                assert_eq!(settings.estimators.len(), 4);
+                assert_eq!(settings.format, OutputFormat::Csv);
+                assert_eq!(settings.threads, 2);
+            }
+            _ => panic!("Failed to parse {:?}.", args),
+        }
+
+        match parse("bench --structure uniform,grid --sizes 10..1000 --csar-limit 50 --seed 2") {
+            Ok(Command::Bench(settings, bench)) => {
+                assert_eq!(settings.seed, 2);
+                let keys: Vec<&str> = bench.structures.iter().map(|f| f.key).collect();
+                assert_eq!(keys, ["uniform", "grid"]);
+                assert_eq!(bench.sizes, [10, 20, 50, 100, 200, 500, 1000]);
+                assert_eq!(bench.naive_limit, 10_000);
+                assert_eq!(bench.csar_limit, 50);
+            }
+            _ => panic!("Failed to parse bench."),
+        }
+        match parse("bench --structure all --sizes 3,30") {
+            Ok(Command::Bench(_, bench)) => {
+                assert_eq!(bench.structures.len(), STRUCTURES.len());
+                assert_eq!(bench.sizes, [3, 30]);
+            }
+            _ => panic!("Failed to parse bench."),
+        }
+        assert!(parse("bench --sizes 100..10").is_err());
+        assert!(parse("bench --structure uniform,tree").is_err());
+        assert!(parse("run --sizes 10").is_err());
+
+        assert!(matches!(parse("verify --seed 1"), Ok(Command::Verify(_))));
+        assert!(parse("run --arms 0").is_err());
+        assert!(parse("run --arms").is_err());
+        assert!(parse("run --structure tree").is_err());
+        assert!(parse("run --params rank").is_err());
+        assert!(parse("run --graph-format csv").is_err());
+        assert!(matches!(
+            parse("dump --target input --arms 10"),
+            Ok(Command::Dump(_, DumpTarget::Input))
+        ));
+        assert!(parse("dump --target tree").is_err());
+        assert!(parse("run --target input").is_err());
+        match parse("run --gaps near-tie:0.2:0.01") {
+            Ok(Command::Run(settings)) => assert_eq!(
+                settings.gap_profile,
+                Some(GapProfile::NearTie {
+                    gap: 0.2,
+                    tie: 0.01
+                })
+            ),
+            _ => panic!("Failed to parse --gaps."),
+        }
+        assert!(matches!(
+            parse("run --algorithm early --distribution bernoulli"),
+            Ok(Command::Run(settings)) if settings.algorithm == AlgorithmType::EarlyDecision
+        ));
+        assert!(parse("run --algorithm greedy").is_err());
+        assert!(parse("run --estimator early").is_err());
+        assert!(parse("run --gaps equal").is_err());
+        assert!(parse("run --gaps steep:0.1").is_err());
+
+        assert!(parse("run --structure uniform --graph net.mtx").is_err());
+        assert!(parse("run --structure grid --graph net.mtx").is_err());
+        match parse("run --structure circuit --graph net.mtx") {
+            Ok(Command::Run(settings)) => {
+                assert_eq!(settings.structure.key, "circuit");
+                assert!(matches!(
+                    settings.graph,
+                    Some((_, GraphFormat::MatrixMarket))
+                ));
+            }
+            _ => panic!("Failed to parse --graph."),
+        }
+        assert!(parse("jump").is_err());
+        assert!(parse("run arms 3").is_err());
+
+        assert!(matches!(
+            parse("sweep exp.toml --output out"),
+            Ok(Command::Sweep(_, _))
+        ));
+        assert!(parse("sweep").is_err());
+    }
+}
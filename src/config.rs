@@ -0,0 +1,166 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::{cli::parse_settings, experiment::Settings};
+
+/// An experiment described in a config file.
+pub struct Experiment {
+    pub name: String,
+    pub settings: Settings,
+}
+
+/// Load the list of experiments from a TOML file, or a JSON file if the extension is `.json`.
+///
+/// The file has an array `experiments` of tables.
+/// Each table takes the same keys as the command-line options without `--`,
+/// and optionally `name`, which defaults to `experiment-<index>`.
+/// The names must be distinct file names without path separators or `..`,
+/// and every experiment must give `seed` so that the sweep can be reproduced.
+///
+/// ```toml
+/// [[experiments]]
+/// name = "pareto"
+/// structure = "circuit"
+/// arms = 200
+/// distribution = "pareto"
+/// estimator = "all"
+/// trials = 100
+/// seed = 42
+/// ```
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Experiment>, String> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+    let root: toml::Value = if is_json {
+        serde_json::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?
+    } else {
+        toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?
+    };
+
+    let tables = root
+        .get("experiments")
+        .and_then(|experiments| experiments.as_array())
+        .ok_or("The config requires an array `experiments`.")?;
+
+    let mut names = HashSet::new();
+    tables
+        .iter()
+        .enumerate()
+        .map(|(i, table)| {
+            let table = table
+                .as_table()
+                .ok_or_else(|| format!("The experiment #{} is not a table.", i))?;
+
+            let mut name = format!("experiment-{}", i);
+            let mut options = Vec::new();
+            for (key, value) in table {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                        value.to_string()
+                    }
+                    _ => {
+                        return Err(format!(
+                            "The value of {:?} in {:?} is not a scalar.",
+                            key, name
+                        ))
+                    }
+                };
+                if key == "name" {
+                    name = value;
+                } else {
+                    options.push((key.clone(), value));
+                }
+            }
+
+            check_name(&name)?;
+            if !names.insert(name.clone()) {
+                return Err(format!("The experiment name {:?} is duplicated.", name));
+            }
+            if !options.iter().any(|(key, _)| key == "seed") {
+                return Err(format!("{}: The config requires `seed`.", name));
+            }
+
+            let settings = parse_settings(&options).map_err(|err| format!("{}: {}", name, err))?;
+            Ok(Experiment { name, settings })
+        })
+        .collect()
+}
+
+/// Check that `name` can be used as a file name in the output directory.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name.contains("..") || name.contains(['/', '\\']) {
+        Err(format!("Invalid experiment name {:?}.", name))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load;
+    use crate::experiment::OutputFormat;
+    use std::fs;
+
+    #[test]
+    fn load_test() {
+        let dir = std::env::temp_dir().join(format!("csar-config-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let toml_path = dir.join("sweep.toml");
+        fs::write(
+            &toml_path,
+            r#"
+[[experiments]]
+name = "small"
+structure = "uniform"
+arms = 20
+seed = 3
+
+[[experiments]]
+trials = 5
+format = "csv"
+seed = 4
+"#,
+        )
+        .unwrap();
+        let experiments = load(&toml_path).unwrap();
+        assert_eq!(experiments.len(), 2);
+        assert_eq!(experiments[0].name, "small");
+        assert_eq!(experiments[0].settings.structure.key, "uniform");
+        assert_eq!(experiments[0].settings.arm_num, 20);
+        assert_eq!(experiments[0].settings.seed, 3);
+        assert_eq!(experiments[1].name, "experiment-1");
+        assert_eq!(experiments[1].settings.trials, 5);
+        assert_eq!(experiments[1].settings.format, OutputFormat::Csv);
+
+        let json_path = dir.join("sweep.json");
+        fs::write(
+            &json_path,
+            r#"{"experiments": [{"name": "json", "arms": 30, "estimator": "all", "seed": 5}]}"#,
+        )
+        .unwrap();
+        let experiments = load(&json_path).unwrap();
+        assert_eq!(experiments[0].name, "json");
+        assert_eq!(experiments[0].settings.arm_num, 30);
+        assert_eq!(experiments[0].settings.estimators.len(), 4);
+
+        fs::write(&json_path, r#"{"experiments": [{"arms": 0, "seed": 1}]}"#).unwrap();
+        assert!(load(&json_path).is_err());
+        for experiments in [
+            // The seed is missing.
+            r#"[{"arms": 10}]"#,
+            r#"[{"name": "../escape", "seed": 1}]"#,
+            r#"[{"name": "a/b", "seed": 1}]"#,
+            r#"[{"name": "a\\b", "seed": 1}]"#,
+            r#"[{"name": "same", "seed": 1}, {"name": "same", "seed": 2}]"#,
+        ] {
+            fs::write(&json_path, format!(r#"{{"experiments": {}}}"#, experiments)).unwrap();
+            assert!(load(&json_path).is_err(), "{} is accepted.", experiments);
+        }
+        fs::write(&json_path, r#"{"experiment": []}"#).unwrap();
+        assert!(load(&json_path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fmt, io};
+
+/// The errors of the algorithms, the structures and the arms.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The parameters of a reward distribution are invalid.
+    InvalidDistribution {
+        distribution: &'static str,
+        reason: String,
+    },
+    /// The arm has no reward to replay.
+    NoRecord { arm: usize },
+    /// The parameters of a gap profile are invalid.
+    InvalidGapProfile { reason: String },
+    /// The weight of the arm is NaN.
+    NanWeight { arm: usize },
+    /// The arm does not exist or has already been contracted or deleted.
+    ArmNotFound { arm: usize },
+    /// No arm remains in the structure.
+    NoArm,
+    /// The structure has no basis.
+    NoBasis,
+    /// The number of arms differs from the one of the structure.
+    ArmNumMismatch { expected: usize, actual: usize },
+    /// The reachability graph of the structure has a cycle.
+    CyclicReachabilityGraph,
+    /// Too many arms remain to enumerate the bases.
+    TooManyArms { arm_num: usize, max: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidDistribution {
+                distribution,
+                reason,
+            } => write!(f, "Invalid {} distribution: {}", distribution, reason),
+            Error::NoRecord { arm } => write!(f, "Arm {} has no record.", arm),
+            Error::InvalidGapProfile { reason } => write!(f, "Invalid gap profile: {}", reason),
+            Error::NanWeight { arm } => write!(f, "The weight of arm {} is NaN.", arm),
+            Error::ArmNotFound { arm } => write!(f, "Arm {} does not remain.", arm),
+            Error::NoArm => write!(f, "No arm remains."),
+            Error::NoBasis => write!(f, "The structure has no basis."),
+            Error::ArmNumMismatch { expected, actual } => write!(
+                f,
+                "The structure has {} arms, but {} are given.",
+                expected, actual
+            ),
+            Error::CyclicReachabilityGraph => write!(f, "The reachability graph has a cycle."),
+            Error::TooManyArms { arm_num, max } => write!(
+                f,
+                "{} arms remain, but at most {} can be enumerated.",
+                arm_num, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
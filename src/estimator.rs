@@ -0,0 +1,300 @@
+use crate::sampler::Sampler;
+
+/// An online estimator of the mean of an arm.
+pub trait MeanEstimator: Clone {
+    /// Update the estimator by the observed value.
+    fn observe(&mut self, val: f64);
+
+    /// Return the estimated mean of all the observed values.
+    /// It takes `&mut self` so that an estimator can solve it lazily and cache the solution.
+    fn estimate(&mut self) -> f64;
+}
+
+/// The empirical mean.
+impl MeanEstimator for Sampler {
+    fn observe(&mut self, val: f64) {
+        Sampler::observe(self, val);
+    }
+
+    fn estimate(&mut self) -> f64 {
+        self.get_mean()
+    }
+}
+
+/// The median of the empirical means of blocks.
+/// Observed values are assigned to the blocks in a round-robin manner.
+#[derive(Clone)]
+pub struct MedianOfMeans {
+    count: u64,
+    block_sums: Vec<f64>,
+}
+
+impl MedianOfMeans {
+    /// Use `ceil(8 ln(1 / delta))` blocks for the confidence `1 - delta`.
+    pub fn new(delta: f64) -> Self {
+        let block_num = (8.0 * (1.0 / delta).ln()).ceil().max(1.0) as usize;
+        MedianOfMeans {
+            count: 0,
+            block_sums: vec![0_f64; block_num],
+        }
+    }
+}
+
+impl MeanEstimator for MedianOfMeans {
+    fn observe(&mut self, val: f64) {
+        let block_num = self.block_sums.len();
+        self.block_sums[(self.count % block_num as u64) as usize] += val;
+        self.count += 1;
+    }
+
+    fn estimate(&mut self) -> f64 {
+        let block_num = self.block_sums.len() as u64;
+
+        // Only blocks with at least one value are taken into account.
+        let mut block_means: Vec<f64> = self
+            .block_sums
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &sum)| {
+                let size = self.count / block_num + u64::from((i as u64) < self.count % block_num);
+                (size > 0).then(|| sum / size as f64)
+            })
+            .collect();
+        if block_means.is_empty() {
+            return 0_f64;
+        }
+
+        // A NaN block mean makes the estimate NaN, which the structure reports as an error.
+        if block_means.iter().any(|mean| mean.is_nan()) {
+            return f64::NAN;
+        }
+        block_means.sort_unstable_by(f64::total_cmp);
+        let mid = block_means.len() / 2;
+        if block_means.len() % 2 == 1 {
+            block_means[mid]
+        } else {
+            (block_means[mid - 1] + block_means[mid]) / 2.0
+        }
+    }
+}
+
+/// Catoni's M-estimator.
+/// It keeps all the observed values, and solves the estimate only when it is requested
+/// after new values, starting from the last solution.
+#[derive(Clone)]
+pub struct Catoni {
+    delta: f64,
+    variance: f64,
+    values: Vec<f64>,
+    /// The minimum and the maximum of the values, which bracket the estimate.
+    min: f64,
+    max: f64,
+    /// The number of values at the last solution.
+    solved_count: usize,
+    theta: f64,
+}
+
+impl Catoni {
+    /// It is required that the variance of rewards is at most `variance`.
+    pub fn new(delta: f64, variance: f64) -> Self {
+        Catoni {
+            delta,
+            variance,
+            values: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            solved_count: 0,
+            theta: 0_f64,
+        }
+    }
+
+    /// The influence function of Catoni and its derivative.
+    fn psi(x: f64) -> (f64, f64) {
+        let denom = 1.0 + x.abs() + x * x / 2.0;
+        (x.signum() * denom.ln(), (1.0 + x.abs()) / denom)
+    }
+
+    /// Solve the estimate from all the values, starting from the last solution.
+    fn solve(&mut self) {
+        let n = self.values.len() as f64;
+        let alpha = (2.0 * (2.0 / self.delta).ln() / (n * self.variance)).sqrt();
+
+        // Find the root of the decreasing score by Newton's method safeguarded by bisection.
+        // The root lies between the minimum and the maximum of the values.
+        let (mut low, mut high) = (self.min, self.max);
+        let mut theta = if self.solved_count > 0 && low < self.theta && self.theta < high {
+            self.theta
+        } else {
+            self.values.iter().sum::<f64>() / n
+        };
+        self.solved_count = self.values.len();
+
+        for _ in 0..100 {
+            let (score, slope) = self.values.iter().fold((0_f64, 0_f64), |(f, df), &x| {
+                let (psi, dpsi) = Self::psi(alpha * (x - theta));
+                (f + psi, df - alpha * dpsi)
+            });
+            if score > 0.0 {
+                low = theta;
+            } else {
+                high = theta;
+            }
+
+            let mut next = theta - score / slope;
+            if !(low < next && next < high) {
+                next = (low + high) / 2.0;
+            }
+            if (next - theta).abs() <= 1e-12 * (1.0 + theta.abs()) {
+                self.theta = next;
+                return;
+            }
+            theta = next;
+        }
+
+        self.theta = theta;
+    }
+}
+
+impl MeanEstimator for Catoni {
+    fn observe(&mut self, val: f64) {
+        self.values.push(val);
+        self.min = self.min.min(val);
+        self.max = self.max.max(val);
+    }
+
+    fn estimate(&mut self) -> f64 {
+        if self.solved_count != self.values.len() {
+            self.solve();
+        }
+        self.theta
+    }
+}
+
+/// The truncated empirical mean of Bubeck, Cesa-Bianchi and Lugosi.
+/// The t-th value is ignored if its absolute value exceeds `(u t / ln(1 / delta))^(1 / (1 + epsilon))`.
+#[derive(Clone)]
+pub struct TruncatedMean {
+    delta: f64,
+    epsilon: f64,
+    moment: f64,
+    count: u64,
+    truncated_sum: f64,
+}
+
+impl TruncatedMean {
+    /// It is required that the raw moment of order `1 + epsilon` of rewards is at most `moment`.
+    pub fn new(delta: f64, epsilon: f64, moment: f64) -> Self {
+        TruncatedMean {
+            delta,
+            epsilon,
+            moment,
+            count: 0,
+            truncated_sum: 0_f64,
+        }
+    }
+}
+
+impl MeanEstimator for TruncatedMean {
+    fn observe(&mut self, val: f64) {
+        self.count += 1;
+        let threshold = (self.moment * self.count as f64 / (1.0 / self.delta).ln())
+            .powf(1.0 / (1.0 + self.epsilon));
+        if val.abs() <= threshold {
+            self.truncated_sum += val;
+        }
+    }
+
+    fn estimate(&mut self) -> f64 {
+        if self.count == 0 {
+            0_f64
+        } else {
+            self.truncated_sum / self.count as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Catoni, MeanEstimator, MedianOfMeans, TruncatedMean};
+    use crate::sampler::Sampler;
+    use rand::{rngs::StdRng, SeedableRng};
+    use rand_distr::{Distribution, Pareto};
+
+    fn estimate_pareto(mut estimator: impl MeanEstimator, rng: &mut StdRng) -> f64 {
+        let distr = Pareto::new(1.0, 3.0).unwrap();
+        for _ in 0..10_000 {
+            estimator.observe(distr.sample(rng));
+        }
+        estimator.estimate()
+    }
+
+    #[test]
+    fn pareto_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let true_mean = 1.5;
+
+        let estimates = [
+            estimate_pareto(Sampler::new(), &mut rng),
+            estimate_pareto(MedianOfMeans::new(0.01), &mut rng),
+            estimate_pareto(Catoni::new(0.01, 0.75), &mut rng),
+            estimate_pareto(TruncatedMean::new(0.01, 1.0, 3.0), &mut rng),
+        ];
+        for estimate in estimates {
+            assert!(
+                (estimate - true_mean).abs() < 0.1,
+                "The estimate {} is far from the true mean {}.",
+                estimate,
+                true_mean
+            );
+        }
+    }
+
+    #[test]
+    fn constant_test() {
+        let mut mom = MedianOfMeans::new(0.01);
+        let mut catoni = Catoni::new(0.01, 1.0);
+        let mut truncated = TruncatedMean::new(0.01, 1.0, 10.0);
+        for _ in 0..10 {
+            mom.observe(0.5);
+            catoni.observe(0.5);
+            truncated.observe(0.5);
+        }
+        assert!((mom.estimate() - 0.5).abs() < 1e-12);
+        assert!((catoni.estimate() - 0.5).abs() < 1e-12);
+        assert!((truncated.estimate() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn catoni_resolve_test() {
+        let mut catoni = Catoni::new(0.01, 1.0);
+        assert_eq!(catoni.estimate(), 0.0);
+        for _ in 0..100 {
+            catoni.observe(1.0);
+        }
+        assert!((catoni.estimate() - 1.0).abs() < 1e-12);
+
+        // Every new value is reflected, and the warm start agrees with a fresh solution.
+        let mut fresh = Catoni::new(0.01, 1.0);
+        for _ in 0..100 {
+            fresh.observe(1.0);
+        }
+        let mut previous = catoni.estimate();
+        for _ in 0..10 {
+            catoni.observe(3.0);
+            fresh.observe(3.0);
+            let estimate = catoni.estimate();
+            assert!(estimate > previous);
+            previous = estimate;
+        }
+        assert!((catoni.estimate() - fresh.estimate()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn nan_test() {
+        let mut mom = MedianOfMeans::new(0.01);
+        for i in 0..10 {
+            mom.observe(if i == 3 { f64::NAN } else { 0.5 });
+        }
+        assert!(mom.estimate().is_nan());
+    }
+}
@@ -0,0 +1,1507 @@
+mod cpu_time;
+pub mod registry;
+mod seed;
+
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use cpu_time::thread_cpu_time;
+use csar::{
+    algorithm::{
+        brute_force::{brute_force, MAX_BRUTE_FORCE_ARMS},
+        csar_with_early_decisions, csar_with_estimator, naive_gaps, naive_maxgap,
+    },
+    arms::{
+        replay::{ReplayArms, ReplayMode},
+        Arm, Arms, BanditEnvironment, RewardFamily, SampleCounter,
+    },
+    error::Error,
+    estimator::{Catoni, MedianOfMeans, TruncatedMean},
+    hardness::{generator::GapProfile, Hardness},
+    sampler::{ConfidenceBound, Sampler},
+    structure::CombinatorialStructure,
+    util::graph::{
+        dot::{DotArms, DotOptions},
+        reader::{EdgeReward, GraphFile, GraphFormat},
+    },
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use registry::{Instance, StructureFactory, StructureParams};
+use seed::derive_seed;
+use serde_json::json;
+
+const EPS: f64 = 1e-15;
+/// The error probability given to the estimators and the confidence bounds.
+const DELTA: f64 = 0.01;
+
+#[derive(Clone)]
+pub enum EnumMeanEstimators {
+    Empirical(Sampler),
+    MedianOfMeans(MedianOfMeans),
+    Catoni(Catoni),
+    TruncatedMean(TruncatedMean),
+}
+
+impl EnumMeanEstimators {
+    /// Every estimator with the parameters for the experiments.
+    pub fn all() -> Vec<Self> {
+        let delta = DELTA;
+        vec![
+            EnumMeanEstimators::Empirical(Sampler::new()),
+            EnumMeanEstimators::MedianOfMeans(MedianOfMeans::new(delta)),
+            // The variance is at most 1 for the synthetic arms except the Student-t and Pareto arms,
+            // whose variance is infinite for the degrees of freedom and the shapes up to 2,
+            // so Catoni's guarantee does not hold for them.
+            EnumMeanEstimators::Catoni(Catoni::new(delta, 1.0)),
+            // The Pareto arms have finite moments of order 1.4,
+            // and they are at most 6 with the parameters of `RewardFamily::Pareto`.
+            EnumMeanEstimators::TruncatedMean(TruncatedMean::new(delta, 0.4, 6.0)),
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnumMeanEstimators::Empirical(_) => "Empirical mean",
+            EnumMeanEstimators::MedianOfMeans(_) => "Median-of-means",
+            EnumMeanEstimators::Catoni(_) => "Catoni's M-estimator",
+            EnumMeanEstimators::TruncatedMean(_) => "Truncated mean",
+        }
+    }
+
+    /// The name used in the command-line arguments.
+    pub fn key(&self) -> &'static str {
+        match self {
+            EnumMeanEstimators::Empirical(_) => "empirical",
+            EnumMeanEstimators::MedianOfMeans(_) => "median-of-means",
+            EnumMeanEstimators::Catoni(_) => "catoni",
+            EnumMeanEstimators::TruncatedMean(_) => "truncated",
+        }
+    }
+}
+
+/// The variant of CSAR run in the experiments.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlgorithmType {
+    /// CSAR deciding one arm in a phase, run with each of the estimators.
+    Csar,
+    /// CSAR deciding several arms in a phase by the confidence bound of the reward distributions,
+    /// run with the empirical mean.
+    EarlyDecision,
+}
+
+impl AlgorithmType {
+    pub const ALL: [AlgorithmType; 2] = [AlgorithmType::Csar, AlgorithmType::EarlyDecision];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlgorithmType::Csar => "CSAR",
+            AlgorithmType::EarlyDecision => "CSAR with early decisions",
+        }
+    }
+
+    /// The name used in the command-line arguments.
+    pub fn key(&self) -> &'static str {
+        match self {
+            AlgorithmType::Csar => "csar",
+            AlgorithmType::EarlyDecision => "early",
+        }
+    }
+}
+
+/// A variant of CSAR with its parameters, run once in each trial.
+#[derive(Clone)]
+pub enum Algorithm {
+    /// CSAR with the mean estimator.
+    Csar(EnumMeanEstimators),
+    /// CSAR with the early decisions by the confidence bound and the error probability.
+    EarlyDecision(ConfidenceBound, f64),
+}
+
+impl Algorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Csar(estimator) => estimator.name(),
+            Algorithm::EarlyDecision(_, _) => "Empirical mean with early decisions",
+        }
+    }
+
+    pub fn algorithm_type(&self) -> AlgorithmType {
+        match self {
+            Algorithm::Csar(_) => AlgorithmType::Csar,
+            Algorithm::EarlyDecision(_, _) => AlgorithmType::EarlyDecision,
+        }
+    }
+
+    /// The key of the mean estimator, which is the empirical mean for the early decisions.
+    pub fn estimator_key(&self) -> &'static str {
+        match self {
+            Algorithm::Csar(estimator) => estimator.key(),
+            Algorithm::EarlyDecision(_, _) => "empirical",
+        }
+    }
+}
+
+/// Where the rewards come from.
+#[derive(Clone)]
+pub enum SourceSetting {
+    /// Generate arms of the family randomly in each trial.
+    Synthetic(RewardFamily),
+    /// Replay the reward logs in the file in every trial.
+    Replay(PathBuf, ReplayMode),
+}
+
+#[derive(Clone)]
+enum RewardSource {
+    Synthetic(RewardFamily),
+    Replay(Box<ReplayArms>),
+    /// The Gaussian arms given by the graph file.
+    Given(Vec<EdgeReward>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// Human-readable logs and a summary.
+    Text,
+    /// One comma-separated row per trial and estimator.
+    /// The summary is written into stderr.
+    Csv,
+    /// A document with the settings, a record per trial and estimator, and the summary.
+    Json,
+}
+
+/// Which graph of an instance is dumped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DumpTarget {
+    /// The underlying graph, whose edges are the arms.
+    Input,
+    /// The reachability graph of the optimal basis.
+    Reachability,
+}
+
+/// The settings of an experiment.
+#[derive(Clone)]
+pub struct Settings {
+    pub structure: &'static StructureFactory,
+    /// The parameters of the structure, where those not given are chosen randomly.
+    pub structure_params: StructureParams,
+    /// The graph file whose circuit matroid replaces the structure above.
+    /// If the file gives the rewards of the edges, they replace the synthetic arms.
+    pub graph: Option<(PathBuf, GraphFormat)>,
+    pub source: SourceSetting,
+    /// Ignored when the rewards are replayed or the graph file is given,
+    /// where they determine the number of arms.
+    pub arm_num: usize,
+    /// The gaps targeted by the means of the synthetic arms, which are random if `None`.
+    pub gap_profile: Option<GapProfile>,
+    pub algorithm: AlgorithmType,
+    /// The estimators compared by CSAR, which must be only the empirical mean for the early decisions.
+    pub estimators: Vec<EnumMeanEstimators>,
+    pub samples_per_phase: usize,
+    pub trials: usize,
+    pub seed: u64,
+    pub format: OutputFormat,
+    /// The number of threads running trials in parallel.
+    /// The results do not depend on it.
+    pub threads: usize,
+}
+
+/// The outcome of a single trial.
+pub struct TrialResult {
+    elapsed: Duration,
+    /// The CPU time of the thread running CSAR.
+    /// It equals `elapsed` if the platform cannot measure it.
+    cpu_time: Duration,
+    /// The simple regret divided by the absolute weight of the true optimal superarm,
+    /// or the simple regret itself if the weight is 0.
+    relative_error: f64,
+    /// Whether the simple regret is negligible relative to the weight of the true optimal superarm.
+    correct: bool,
+    /// The weight of the true optimal superarm minus that of the superarm found by CSAR.
+    simple_regret: f64,
+    /// The number of arms found by CSAR but not in the true optimal superarm.
+    wrongly_accepted: usize,
+    /// The number of arms in the true optimal superarm but not found by CSAR.
+    wrongly_rejected: usize,
+    /// The hardness of the instance computed from the true gaps.
+    hardness: Hardness,
+    /// The number of samples drawn by CSAR.
+    samples: u64,
+}
+
+/// Run a variant of CSAR once on `structure` against the environment `env`.
+/// `csar` runs on a clone of `structure` and the environment counting the samples.
+/// It is required that the true means of `env` are known.
+fn run_trial<Structure: CombinatorialStructure, Env: BanditEnvironment>(
+    structure: &Structure,
+    env: &mut Env,
+    csar: impl FnOnce(Structure, &mut SampleCounter<Env>) -> Result<Vec<usize>, Error>,
+) -> Result<TrialResult, Error> {
+    let means = env
+        .get_means()
+        .expect("[ERROR] The true means of the environment are unknown.");
+    let mut env = SampleCounter::new(env);
+
+    // Execute CSAR.
+    // Measure the elapsed time and the CPU time.
+    let start_time = Instant::now();
+    let start_cpu_time = thread_cpu_time();
+    let csar_optimal = csar(structure.clone(), &mut env)?;
+    let elapsed = start_time.elapsed();
+    let cpu_time = match (start_cpu_time, thread_cpu_time()) {
+        (Some(start), Some(end)) => end.saturating_sub(start),
+        _ => elapsed,
+    };
+    let csar_weight: f64 = csar_optimal.iter().map(|&i| means[i]).sum();
+
+    // Find the true optimal superarm.
+    let true_optimal = structure.optimal(&means).ok_or(Error::NoBasis)?;
+    let true_weight: f64 = true_optimal.iter().map(|&i| means[i]).sum();
+    let simple_regret = true_weight - csar_weight;
+
+    // The Hamming distance between the superarms.
+    let mut in_true_optimal = vec![false; means.len()];
+    for &i in &true_optimal {
+        in_true_optimal[i] = true;
+    }
+    let wrongly_accepted = csar_optimal
+        .iter()
+        .filter(|&&i| !in_true_optimal[i])
+        .count();
+    let wrongly_rejected = true_optimal.len() + wrongly_accepted - csar_optimal.len();
+
+    Ok(TrialResult {
+        elapsed,
+        cpu_time,
+        relative_error: if true_weight == 0.0 {
+            simple_regret
+        } else {
+            simple_regret / true_weight.abs()
+        },
+        // The weights may be negative, so the sign of the relative error is meaningless.
+        correct: simple_regret <= EPS * true_weight.abs().max(1.0),
+        simple_regret,
+        wrongly_accepted,
+        wrongly_rejected,
+        hardness: Hardness::new(structure, &means)?,
+        samples: env.get_count(),
+    })
+}
+
+fn run_trial_on(
+    structure: &impl CombinatorialStructure,
+    algorithm: &Algorithm,
+    samples_per_phase: usize,
+    env: &mut impl BanditEnvironment,
+) -> Result<TrialResult, Error> {
+    let estimator = match algorithm {
+        Algorithm::Csar(estimator) => estimator,
+        Algorithm::EarlyDecision(bound, delta) => {
+            return run_trial(structure, env, |structure, env| {
+                csar_with_early_decisions(structure, env, samples_per_phase, *bound, *delta)
+            })
+        }
+    };
+    match estimator {
+        EnumMeanEstimators::Empirical(e) => run_trial(structure, env, |structure, env| {
+            csar_with_estimator(structure, env, e, samples_per_phase)
+        }),
+        EnumMeanEstimators::MedianOfMeans(e) => run_trial(structure, env, |structure, env| {
+            csar_with_estimator(structure, env, e, samples_per_phase)
+        }),
+        EnumMeanEstimators::Catoni(e) => run_trial(structure, env, |structure, env| {
+            csar_with_estimator(structure, env, e, samples_per_phase)
+        }),
+        EnumMeanEstimators::TruncatedMean(e) => run_trial(structure, env, |structure, env| {
+            csar_with_estimator(structure, env, e, samples_per_phase)
+        }),
+    }
+}
+
+/// Return the algorithms run in each trial, which are CSAR with every estimator
+/// or CSAR with the early decisions.
+/// The early decisions take the confidence bound from the distribution of the rewards,
+/// and they are refused for the heavy-tailed and replayed rewards.
+fn algorithms(settings: &Settings, source: &RewardSource) -> io::Result<Vec<Algorithm>> {
+    if settings.algorithm == AlgorithmType::Csar {
+        return Ok(settings
+            .estimators
+            .iter()
+            .cloned()
+            .map(Algorithm::Csar)
+            .collect());
+    }
+
+    if !matches!(
+        settings.estimators.as_slice(),
+        [EnumMeanEstimators::Empirical(_)]
+    ) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The early decisions use only the empirical mean.",
+        ));
+    }
+    let bound = match source {
+        RewardSource::Synthetic(family) => family.confidence_bound().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The early decisions have no confidence bound for the {} rewards.",
+                    family.name()
+                ),
+            )
+        })?,
+        // The Gaussian rewards are sub-Gaussian with their standard deviations.
+        RewardSource::Given(rewards) => ConfidenceBound::SubGaussian {
+            sigma: rewards.iter().fold(0.0, |sigma, r| r.std_dev.max(sigma)),
+        },
+        RewardSource::Replay(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The early decisions have no confidence bound for the replayed rewards.",
+            ))
+        }
+    };
+    Ok(vec![Algorithm::EarlyDecision(bound, DELTA)])
+}
+
+/// The Wilson score interval of the success probability with `z` standard deviations.
+fn wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0_f64, 1_f64);
+    }
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half_width = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+/// The quantile of the standard normal distribution for the 95% confidence intervals.
+const Z_95: f64 = 1.959963984540054;
+
+/// The aggregated results of an estimator.
+#[derive(Default)]
+struct Summary {
+    trials: usize,
+    correct_count: usize,
+    total_elapsed: Duration,
+    total_cpu_time: Duration,
+    total_samples: u64,
+    total_simple_regret: f64,
+    total_wrongly_accepted: usize,
+    total_wrongly_rejected: usize,
+    total_h1: f64,
+    total_h2: f64,
+}
+
+impl Summary {
+    fn add(&mut self, result: &TrialResult, correct: bool) {
+        self.trials += 1;
+        self.correct_count += usize::from(correct);
+        self.total_elapsed += result.elapsed;
+        self.total_cpu_time += result.cpu_time;
+        self.total_samples += result.samples;
+        self.total_simple_regret += result.simple_regret;
+        self.total_wrongly_accepted += result.wrongly_accepted;
+        self.total_wrongly_rejected += result.wrongly_rejected;
+        self.total_h1 += result.hardness.h1;
+        self.total_h2 += result.hardness.h2;
+    }
+
+    /// The average of `total` over the trials.
+    fn average(&self, total: f64) -> f64 {
+        total / self.trials.max(1) as f64
+    }
+
+    fn average_elapsed_millis(&self) -> f64 {
+        self.total_elapsed.as_secs_f64() * 1e3 / self.trials.max(1) as f64
+    }
+
+    fn average_cpu_millis(&self) -> f64 {
+        self.total_cpu_time.as_secs_f64() * 1e3 / self.trials.max(1) as f64
+    }
+
+    fn average_samples(&self) -> f64 {
+        self.total_samples as f64 / self.trials.max(1) as f64
+    }
+}
+
+/// Randomly generate `arm_num` arms of `family` for `structure`,
+/// whose means target the gap profile of the settings if given.
+fn synthetic_arms(
+    settings: &Settings,
+    structure: &dyn Instance,
+    family: RewardFamily,
+    arm_num: usize,
+    rng: &mut StdRng,
+) -> Result<Vec<Arm>, Error> {
+    match &settings.gap_profile {
+        Some(profile) => structure
+            .profile_means(profile, rng)?
+            .into_iter()
+            .map(|mean| family.arm_with_mean(mean, rng))
+            .collect(),
+        None => Ok((0..arm_num).map(|_| family.sample_arm(rng)).collect()),
+    }
+}
+
+/// Run every algorithm in the `trial`-th trial.
+fn run_algorithms(
+    settings: &Settings,
+    algorithms: &[Algorithm],
+    structure: &dyn Instance,
+    source: &mut RewardSource,
+    arm_num: usize,
+    trial: usize,
+) -> Result<Vec<TrialResult>, Error> {
+    // Each trial has its own seed so that it can be reproduced alone.
+    let trial_seed = derive_seed(settings.seed, trial as u64);
+    let samples_per_phase = settings.samples_per_phase;
+
+    algorithms
+        .iter()
+        .map(|algorithm| {
+            // Every algorithm faces the same arms and the same rewards.
+            let mut trial_rng = StdRng::seed_from_u64(trial_seed);
+
+            let result = match source {
+                RewardSource::Synthetic(family) => {
+                    // Generate arms randomly.
+                    let mut arms = Arms::new(trial_rng.gen());
+                    for arm in
+                        synthetic_arms(settings, structure, *family, arm_num, &mut trial_rng)?
+                    {
+                        arms.push_arm(arm);
+                    }
+                    structure.run_trial(algorithm, samples_per_phase, &mut arms)?
+                }
+                RewardSource::Replay(replay_arms) => structure.run_trial(
+                    algorithm,
+                    samples_per_phase,
+                    replay_arms.restart(trial_seed),
+                )?,
+                RewardSource::Given(rewards) => {
+                    let mut arms = Arms::new(trial_rng.gen());
+                    for reward in rewards.iter() {
+                        arms.push_arm(Arm::gaussian(reward.mean, reward.std_dev)?);
+                    }
+                    structure.run_trial(algorithm, samples_per_phase, &mut arms)?
+                }
+            };
+
+            // The elapsed time.
+            eprintln!(
+                "[INFO] {}: Elapsed time: {} ms (trial #{})",
+                algorithm.name(),
+                result.elapsed.as_millis(),
+                trial
+            );
+
+            // Check the simple regret.
+            if result.correct {
+                eprintln!(
+                    "[RESULT] {}: Correct. The relative error = {:.20} (trial #{})",
+                    algorithm.name(),
+                    result.relative_error,
+                    trial
+                );
+            } else {
+                eprintln!(
+                    "[RESULT] {}: Wrong. The relative error = {:.20} (trial #{}, trial seed {})",
+                    algorithm.name(),
+                    result.relative_error,
+                    trial,
+                    trial_seed
+                );
+            }
+
+            Ok(result)
+        })
+        .collect()
+}
+
+/// Run every trial on `settings.threads` threads.
+/// The results are indexed by trials and then algorithms, and they do not depend on the number of threads.
+fn run_trials(
+    settings: &Settings,
+    algorithms: &[Algorithm],
+    structure: &dyn Instance,
+    source: &RewardSource,
+    arm_num: usize,
+) -> Result<Vec<Vec<TrialResult>>, Error> {
+    let next_trial = AtomicUsize::new(0);
+    let mut results: Vec<Option<Result<Vec<TrialResult>, Error>>> =
+        (0..settings.trials).map(|_| None).collect();
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..settings.threads.clamp(1, settings.trials))
+            .map(|_| {
+                // Each thread replays the logs independently.
+                let mut source = source.clone();
+                let next_trial = &next_trial;
+                scope.spawn(move || {
+                    let mut finished = Vec::new();
+                    loop {
+                        let trial = next_trial.fetch_add(1, Ordering::Relaxed);
+                        if trial >= settings.trials {
+                            break;
+                        }
+                        let result = run_algorithms(
+                            settings,
+                            algorithms,
+                            structure,
+                            &mut source,
+                            arm_num,
+                            trial,
+                        );
+                        finished.push((trial, result));
+                    }
+                    finished
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            for (trial, result) in worker.join().expect("[ERROR] A trial panicked.") {
+                results[trial] = Some(result);
+            }
+        }
+    });
+
+    // Report the error of the first failed trial.
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Load the graph file in the settings if given.
+fn load_graph(settings: &Settings) -> io::Result<Option<GraphFile>> {
+    settings
+        .graph
+        .as_ref()
+        .map(|(path, format)| GraphFile::from_file(path, *format))
+        .transpose()
+}
+
+/// Build an instance of the structure in the settings with `arm_num` arms,
+/// or the circuit matroid of `graph_file` if given.
+fn build_structure(
+    settings: &Settings,
+    graph_file: Option<&GraphFile>,
+    arm_num: usize,
+    rng: &mut StdRng,
+) -> io::Result<Box<dyn Instance>> {
+    match graph_file {
+        Some(file) if file.graph.get_edges().len() != arm_num => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "The graph has {} edges, but there are {} arms.",
+                file.graph.get_edges().len(),
+                arm_num
+            ),
+        )),
+        Some(file) => Ok(registry::circuit_instance(&file.graph)),
+        None => settings
+            .structure
+            .build(arm_num, &settings.structure_params, rng)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err)),
+    }
+}
+
+/// Run CSAR repeatedly on a randomly sampled instance and report how often it is correct.
+/// The results are written into `out` in the format of the settings.
+pub fn run(settings: &Settings, out: &mut impl Write) -> io::Result<()> {
+    let graph_file = load_graph(settings)?;
+    let source = match (&settings.source, &graph_file) {
+        (SourceSetting::Replay(path, mode), _) => {
+            RewardSource::Replay(Box::new(ReplayArms::from_file(path, *mode)?))
+        }
+        (
+            SourceSetting::Synthetic(_),
+            Some(GraphFile {
+                rewards: Some(rewards),
+                ..
+            }),
+        ) => RewardSource::Given(rewards.clone()),
+        (SourceSetting::Synthetic(family), _) => RewardSource::Synthetic(*family),
+    };
+    if settings.gap_profile.is_some() && !matches!(source, RewardSource::Synthetic(_)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The gap profile requires synthetic arms.",
+        ));
+    }
+    let arm_num = match (&source, &graph_file) {
+        (RewardSource::Replay(replay_arms), _) => replay_arms.get_arm_num(),
+        (_, Some(file)) => file.graph.get_edges().len(),
+        (RewardSource::Synthetic(_) | RewardSource::Given(_), None) => settings.arm_num,
+    };
+    let algorithms = algorithms(settings, &source)?;
+    let seed = settings.seed;
+
+    // Sample an instance randomly.
+    let mut rng = StdRng::seed_from_u64(seed);
+    let structure = build_structure(settings, graph_file.as_ref(), arm_num, &mut rng)?;
+    eprintln!(
+        "[INFO] An instance of {} has been randomly sampled.",
+        structure.type_name()
+    );
+
+    // The graphs of fixed shapes may have a different number of arms from the requested one,
+    // which only the synthetic arms can follow.
+    if !matches!(source, RewardSource::Synthetic(_)) && structure.get_arm_num() != arm_num {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "The structure has {} arms, but the rewards are given to {} arms.",
+                structure.get_arm_num(),
+                arm_num
+            ),
+        ));
+    }
+    let arm_num = structure.get_arm_num();
+
+    let structure_key = match graph_file {
+        Some(_) => "graph",
+        None => settings.structure.key,
+    };
+    let params = structure.params();
+    let params_str = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>()
+        .join(";");
+
+    let start_time = Instant::now();
+    let results = run_trials(settings, &algorithms, structure.as_ref(), &source, arm_num)?;
+    let wall_clock_time = start_time.elapsed();
+
+    if settings.format == OutputFormat::Csv {
+        writeln!(
+            out,
+            "trial,trial_seed,seed,structure,structure_params,algorithm,estimator,\
+             elapsed_ms,cpu_ms,correct,relative_error,samples,\
+             simple_regret,wrongly_accepted,wrongly_rejected,h1,h2,width,csar_hardness"
+        )?;
+    }
+
+    let mut summaries: Vec<Summary> = algorithms.iter().map(|_| Summary::default()).collect();
+    let mut json_trials = Vec::new();
+
+    for (trial, trial_results) in results.iter().enumerate() {
+        let trial_seed = derive_seed(seed, trial as u64);
+
+        for (k, (algorithm, result)) in algorithms.iter().zip(trial_results).enumerate() {
+            let correct = result.correct;
+            let elapsed_millis = result.elapsed.as_secs_f64() * 1e3;
+            let cpu_millis = result.cpu_time.as_secs_f64() * 1e3;
+
+            match settings.format {
+                OutputFormat::Text => {}
+                OutputFormat::Csv => writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{:.3},{:.3},{},{:e},{},{:e},{},{},{:e},{:e},{},{:e}",
+                    trial,
+                    trial_seed,
+                    seed,
+                    structure_key,
+                    params_str,
+                    algorithm.algorithm_type().key(),
+                    algorithm.estimator_key(),
+                    elapsed_millis,
+                    cpu_millis,
+                    correct,
+                    result.relative_error,
+                    result.samples,
+                    result.simple_regret,
+                    result.wrongly_accepted,
+                    result.wrongly_rejected,
+                    result.hardness.h1,
+                    result.hardness.h2,
+                    result.hardness.width,
+                    result.hardness.csar()
+                )?,
+                OutputFormat::Json => json_trials.push(json!({
+                    "trial": trial,
+                    "trial_seed": trial_seed,
+                    "algorithm": algorithm.algorithm_type().key(),
+                    "estimator": algorithm.estimator_key(),
+                    "elapsed_ms": elapsed_millis,
+                    "cpu_ms": cpu_millis,
+                    "correct": correct,
+                    "relative_error": result.relative_error,
+                    "samples": result.samples,
+                    "simple_regret": result.simple_regret,
+                    "wrongly_accepted": result.wrongly_accepted,
+                    "wrongly_rejected": result.wrongly_rejected,
+                    "h1": result.hardness.h1,
+                    "h2": result.hardness.h2,
+                    "width": result.hardness.width,
+                    "csar_hardness": result.hardness.csar(),
+                })),
+            }
+
+            summaries[k].add(result, correct);
+        }
+    }
+
+    let mut json_summaries = Vec::new();
+    for (algorithm, summary) in algorithms.iter().zip(&summaries) {
+        let (ci_low, ci_high) = wilson_interval(summary.correct_count, summary.trials, Z_95);
+
+        match settings.format {
+            OutputFormat::Text | OutputFormat::Csv => {
+                let text = format!(
+                    r"[SUMMARY]
+    Algorithm           : {}
+    Estimator           : {}
+    Average elapsed time: {:.0} ms
+    Average CPU time    : {:.0} ms
+    Average samples     : {:.0}
+    Total samples       : {}
+    Accepted Ratio      : {}/{}
+    95% CI of accuracy  : [{:.4}, {:.4}]
+    Average regret      : {:.4e}
+    Average wrong arms  : {:.2} accepted, {:.2} rejected
+    Average H1          : {:.4e}
+    Average H2          : {:.4e}",
+                    algorithm.algorithm_type().name(),
+                    algorithm.name(),
+                    summary.average_elapsed_millis(),
+                    summary.average_cpu_millis(),
+                    summary.average_samples(),
+                    summary.total_samples,
+                    summary.correct_count,
+                    summary.trials,
+                    ci_low,
+                    ci_high,
+                    summary.average(summary.total_simple_regret),
+                    summary.average(summary.total_wrongly_accepted as f64),
+                    summary.average(summary.total_wrongly_rejected as f64),
+                    summary.average(summary.total_h1),
+                    summary.average(summary.total_h2)
+                );
+                if settings.format == OutputFormat::Text {
+                    writeln!(out, "{}", text)?;
+                } else {
+                    eprintln!("{}", text);
+                }
+            }
+            OutputFormat::Json => json_summaries.push(json!({
+                "algorithm": algorithm.algorithm_type().key(),
+                "estimator": algorithm.estimator_key(),
+                "trials": summary.trials,
+                "correct": summary.correct_count,
+                "accuracy": summary.correct_count as f64 / summary.trials.max(1) as f64,
+                "accuracy_ci_95": [ci_low, ci_high],
+                "average_elapsed_ms": summary.average_elapsed_millis(),
+                "average_cpu_ms": summary.average_cpu_millis(),
+                "average_samples": summary.average_samples(),
+                "total_samples": summary.total_samples,
+                "average_simple_regret": summary.average(summary.total_simple_regret),
+                "average_wrongly_accepted": summary.average(summary.total_wrongly_accepted as f64),
+                "average_wrongly_rejected": summary.average(summary.total_wrongly_rejected as f64),
+                "average_h1": summary.average(summary.total_h1),
+                "average_h2": summary.average(summary.total_h2),
+            })),
+        }
+    }
+
+    let wall_clock_text = format!(
+        "[INFO] Wall-clock time: {:.0} ms with {} thread(s)",
+        wall_clock_time.as_secs_f64() * 1e3,
+        settings.threads
+    );
+    match settings.format {
+        OutputFormat::Text => writeln!(out, "{}", wall_clock_text)?,
+        OutputFormat::Csv | OutputFormat::Json => eprintln!("{}", wall_clock_text),
+    }
+
+    if settings.format == OutputFormat::Json {
+        let document = json!({
+            "seed": seed,
+            "structure": structure_key,
+            "structure_params": params
+                .iter()
+                .map(|&(key, value)| (key.to_string(), json!(value)))
+                .collect::<serde_json::Map<String, serde_json::Value>>(),
+            "samples_per_phase": settings.samples_per_phase,
+            "threads": settings.threads,
+            "wall_clock_ms": wall_clock_time.as_secs_f64() * 1e3,
+            "trials": json_trials,
+            "summary": json_summaries,
+        });
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// The arms found by `naive_maxgap` and `fast_maxgap`, and their elapsed time in microseconds.
+pub type MaxgapComparison = ((usize, u128), (usize, u128));
+
+fn compare_maxgap(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+) -> Result<MaxgapComparison, Error> {
+    let start_time = Instant::now();
+    let naive_arm = naive_maxgap(structure, weights)?;
+    let naive_elapsed = start_time.elapsed().as_micros();
+
+    let start_time = Instant::now();
+    let fast_arm = structure.fast_maxgap(weights)?;
+    let fast_elapsed = start_time.elapsed().as_micros();
+
+    Ok(((naive_arm, naive_elapsed), (fast_arm, fast_elapsed)))
+}
+
+/// Judge whether or not `a` and `b` agree up to the rounding errors of the sums.
+fn approx_eq(a: f64, b: f64) -> bool {
+    a == b || (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Check `optimal` and `naive_gaps` against the enumeration of all bases.
+/// Return `Ok(false)` without checking if more than `MAX_BRUTE_FORCE_ARMS` arms remain,
+/// and the description of the disagreement if any.
+fn check_brute_force(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+) -> Result<bool, String> {
+    if structure.get_arms().len() > MAX_BRUTE_FORCE_ARMS {
+        return Ok(false);
+    }
+
+    let (optimal, expected) = match (structure.optimal(weights), brute_force(structure, weights)) {
+        (None, Err(Error::NoBasis)) => return Ok(true),
+        (Some(optimal), Ok(expected)) => (optimal, expected),
+        (optimal, expected) => {
+            return Err(format!(
+                "optimal = {:?}, but the brute force finds {:?}",
+                optimal,
+                expected.map(|expected| expected.optimal)
+            ))
+        }
+    };
+
+    let weight: f64 = optimal.iter().map(|&i| weights[i]).sum();
+    if !approx_eq(weight, expected.optimal_weight) {
+        return Err(format!(
+            "optimal = {:?} of weight {}, but the brute force finds {:?} of weight {}",
+            optimal, weight, expected.optimal, expected.optimal_weight
+        ));
+    }
+
+    let gaps = naive_gaps(structure, weights).map_err(|err| err.to_string())?;
+    for i in structure.get_arms() {
+        if !approx_eq(gaps[i], expected.gaps[i]) {
+            return Err(format!(
+                "naive_gaps = {} for arm {}, but the brute force finds {}",
+                gaps[i], i, expected.gaps[i]
+            ));
+        }
+    }
+
+    Ok(true)
+}
+
+/// Sample an instance with random weights for each trial, and apply `f` to them.
+/// Return the number of arms and the results.
+fn maxgap_trials<T>(
+    settings: &Settings,
+    f: impl Fn(&dyn Instance, &[f64]) -> Result<T, Error>,
+) -> io::Result<(usize, Vec<T>)> {
+    let graph_file = load_graph(settings)?;
+    let arm_num = graph_file
+        .as_ref()
+        .map_or(settings.arm_num, |file| file.graph.get_edges().len());
+
+    // The number of arms of the instances, which may differ from the requested one.
+    let mut instance_arm_num = arm_num;
+    let results = (0..settings.trials)
+        .map(|trial| {
+            let mut rng = StdRng::seed_from_u64(derive_seed(settings.seed, trial as u64));
+            let structure = build_structure(settings, graph_file.as_ref(), arm_num, &mut rng)?;
+            instance_arm_num = structure.get_arm_num();
+            let weights: Vec<f64> = (0..instance_arm_num).map(|_| rng.gen()).collect();
+            Ok(f(structure.as_ref(), &weights)?)
+        })
+        .collect::<io::Result<_>>()?;
+    Ok((instance_arm_num, results))
+}
+
+/// Draw the graph of `structure` in the DOT language,
+/// highlighting the optimal basis for `weights` and annotating the gaps.
+/// Return `None` if there is no such graph.
+fn dump_dot(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+    target: DumpTarget,
+) -> Result<Option<String>, Error> {
+    let basis = structure.optimal(weights).ok_or(Error::NoBasis)?;
+    // The naive gaps are the reference to debug the reachability graph.
+    let gaps = naive_gaps(structure, weights)?;
+
+    Ok(match target {
+        DumpTarget::Input => structure.underlying_graph().map(|graph| {
+            graph.to_dot(&DotOptions {
+                directed: false,
+                arms: DotArms::Edges(structure.get_arms()),
+                highlighted: basis,
+                gaps: Some(gaps),
+            })
+        }),
+        DumpTarget::Reachability => {
+            Some(structure.reachability_graph(&basis).to_dot(&DotOptions {
+                directed: true,
+                arms: DotArms::Vertices(structure.get_arm_num()),
+                highlighted: basis,
+                gaps: Some(gaps),
+            }))
+        }
+    })
+}
+
+/// Write a graph of an instance in the DOT language into `out`.
+/// The weights are the means in the graph file if given, and random otherwise.
+pub fn dump(settings: &Settings, target: DumpTarget, out: &mut impl Write) -> io::Result<()> {
+    let graph_file = load_graph(settings)?;
+    let arm_num = graph_file
+        .as_ref()
+        .map_or(settings.arm_num, |file| file.graph.get_edges().len());
+
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let structure = build_structure(settings, graph_file.as_ref(), arm_num, &mut rng)?;
+    let weights: Vec<f64> = match graph_file.as_ref().and_then(|file| file.rewards.as_ref()) {
+        Some(rewards) => rewards.iter().map(|reward| reward.mean).collect(),
+        None => (0..structure.get_arm_num()).map(|_| rng.gen()).collect(),
+    };
+
+    let dot = structure.to_dot(&weights, target)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has no graph to dump.", settings.structure.name),
+        )
+    })?;
+    out.write_all(dot.as_bytes())
+}
+
+/// The settings of `bench` in addition to those of the experiment.
+#[derive(Clone)]
+pub struct BenchSettings {
+    /// The numbers of arms, where that of the settings is used if empty.
+    pub sizes: Vec<usize>,
+    /// The structures, where that of the settings is used if empty.
+    pub structures: Vec<&'static StructureFactory>,
+    /// `naive_maxgap` is measured only on the instances with at most this number of arms.
+    pub naive_limit: usize,
+    /// CSAR is measured only on the instances with at most this number of arms.
+    pub csar_limit: usize,
+}
+
+impl Default for BenchSettings {
+    fn default() -> Self {
+        BenchSettings {
+            sizes: Vec::new(),
+            structures: Vec::new(),
+            naive_limit: 10_000,
+            csar_limit: 1_000,
+        }
+    }
+}
+
+/// The elapsed time of the operations on an instance.
+/// The measurements skipped for large instances are `None`.
+pub struct BenchTimes {
+    naive_maxgap: Option<Duration>,
+    fast_maxgap: Duration,
+    reachability_graph: Duration,
+}
+
+/// Measure the elapsed time of `naive_maxgap` if `naive`, `fast_maxgap`,
+/// and `reachability_graph` of the optimal basis for `weights`.
+fn time_maxgap(
+    structure: &impl CombinatorialStructure,
+    weights: &[f64],
+    naive: bool,
+) -> Result<BenchTimes, Error> {
+    let naive_maxgap = if naive {
+        let start_time = Instant::now();
+        naive_maxgap(structure, weights)?;
+        Some(start_time.elapsed())
+    } else {
+        None
+    };
+
+    let start_time = Instant::now();
+    structure.fast_maxgap(weights)?;
+    let fast_maxgap = start_time.elapsed();
+
+    let basis = structure.optimal(weights).ok_or(Error::NoBasis)?;
+    let start_time = Instant::now();
+    structure.reachability_graph(&basis);
+    let reachability_graph = start_time.elapsed();
+
+    Ok(BenchTimes {
+        naive_maxgap,
+        fast_maxgap,
+        reachability_graph,
+    })
+}
+
+/// The average of the measurements in microseconds, or `None` if there is none.
+fn average_micros(times: &[Duration]) -> Option<f64> {
+    if times.is_empty() {
+        return None;
+    }
+    Some(times.iter().sum::<Duration>().as_secs_f64() * 1e6 / times.len() as f64)
+}
+
+/// Format the elapsed time in microseconds, which is empty if skipped.
+fn format_micros(time: Option<Duration>) -> String {
+    time.map_or_else(String::new, |time| {
+        format!("{:.3}", time.as_secs_f64() * 1e6)
+    })
+}
+
+/// Measure the elapsed time of `naive_maxgap`, `fast_maxgap`, `reachability_graph` and CSAR
+/// for every structure and number of arms in `bench_settings`.
+/// The means of the synthetic arms are the weights for the maxgap computations.
+/// The results are written into `out` in the format of the settings,
+/// one record per structure, number of arms and trial for CSV and JSON.
+pub fn bench(
+    settings: &Settings,
+    bench_settings: &BenchSettings,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let family = match settings.source {
+        SourceSetting::Synthetic(family) => family,
+        SourceSetting::Replay(_, _) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "bench requires synthetic arms.",
+            ))
+        }
+    };
+    if settings.estimators.len() > 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "bench measures CSAR with a single estimator.",
+        ));
+    }
+    // The graph file gives the only structure.
+    if settings.graph.is_some() && bench_settings.structures.len() > 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "bench compares no structures with the graph file.",
+        ));
+    }
+    let graph_file = load_graph(settings)?;
+    let structures = match bench_settings.structures.as_slice() {
+        [] => vec![settings.structure],
+        structures => structures.to_vec(),
+    };
+    let sizes = match (&graph_file, bench_settings.sizes.as_slice()) {
+        (Some(file), _) => vec![file.graph.get_edges().len()],
+        (None, []) => vec![settings.arm_num],
+        (None, sizes) => sizes.to_vec(),
+    };
+    let algorithm = &algorithms(settings, &RewardSource::Synthetic(family))?[0];
+
+    if settings.format == OutputFormat::Csv {
+        writeln!(
+            out,
+            "structure,structure_params,requested_arms,arms,trial,trial_seed,\
+             naive_maxgap_us,fast_maxgap_us,reachability_graph_us,csar_us,csar_samples"
+        )?;
+    }
+    let mut json_records = Vec::new();
+
+    for factory in structures {
+        let settings = Settings {
+            structure: factory,
+            ..settings.clone()
+        };
+        let structure_key = match graph_file {
+            Some(_) => "graph",
+            None => factory.key,
+        };
+
+        for &requested_arm_num in &sizes {
+            eprintln!("[INFO] {} with {} arms.", factory.name, requested_arm_num);
+            let mut arm_num = requested_arm_num;
+            let mut naive_times = Vec::new();
+            let mut fast_times = Vec::new();
+            let mut reachability_times = Vec::new();
+            let mut csar_times = Vec::new();
+
+            for trial in 0..settings.trials {
+                let trial_seed = derive_seed(settings.seed, trial as u64);
+                let mut rng = StdRng::seed_from_u64(trial_seed);
+                let structure =
+                    build_structure(&settings, graph_file.as_ref(), requested_arm_num, &mut rng)?;
+                arm_num = structure.get_arm_num();
+
+                let mut arms = Arms::new(rng.gen());
+                let mut weights = Vec::with_capacity(arm_num);
+                for arm in synthetic_arms(&settings, structure.as_ref(), family, arm_num, &mut rng)?
+                {
+                    weights.push(arm.get_mean());
+                    arms.push_arm(arm);
+                }
+
+                let times =
+                    structure.time_maxgap(&weights, arm_num <= bench_settings.naive_limit)?;
+                let csar_result = if arm_num <= bench_settings.csar_limit {
+                    Some(structure.run_trial(algorithm, settings.samples_per_phase, &mut arms)?)
+                } else {
+                    None
+                };
+                let csar_time = csar_result.as_ref().map(|result| result.elapsed);
+                let csar_samples = csar_result.as_ref().map(|result| result.samples);
+
+                match settings.format {
+                    OutputFormat::Text => {}
+                    OutputFormat::Csv => writeln!(
+                        out,
+                        "{},{},{},{},{},{},{},{},{},{},{}",
+                        structure_key,
+                        structure
+                            .params()
+                            .iter()
+                            .map(|(key, value)| format!("{}={}", key, value))
+                            .collect::<Vec<String>>()
+                            .join(";"),
+                        requested_arm_num,
+                        arm_num,
+                        trial,
+                        trial_seed,
+                        format_micros(times.naive_maxgap),
+                        format_micros(Some(times.fast_maxgap)),
+                        format_micros(Some(times.reachability_graph)),
+                        format_micros(csar_time),
+                        csar_samples.map_or_else(String::new, |samples| samples.to_string())
+                    )?,
+                    OutputFormat::Json => json_records.push(json!({
+                        "structure": structure_key,
+                        "structure_params": structure
+                            .params()
+                            .iter()
+                            .map(|&(key, value)| (key.to_string(), json!(value)))
+                            .collect::<serde_json::Map<String, serde_json::Value>>(),
+                        "requested_arms": requested_arm_num,
+                        "arms": arm_num,
+                        "trial": trial,
+                        "trial_seed": trial_seed,
+                        "naive_maxgap_us": times.naive_maxgap.map(|t| t.as_secs_f64() * 1e6),
+                        "fast_maxgap_us": times.fast_maxgap.as_secs_f64() * 1e6,
+                        "reachability_graph_us": times.reachability_graph.as_secs_f64() * 1e6,
+                        "csar_us": csar_time.map(|t| t.as_secs_f64() * 1e6),
+                        "csar_samples": csar_samples,
+                    })),
+                }
+
+                naive_times.extend(times.naive_maxgap);
+                fast_times.push(times.fast_maxgap);
+                reachability_times.push(times.reachability_graph);
+                csar_times.extend(csar_time);
+            }
+
+            let format_average = |times: &[Duration]| {
+                average_micros(times).map_or_else(
+                    || "skipped".to_string(),
+                    |average| format!("{:.1} us", average),
+                )
+            };
+            let text = format!(
+                r"[SUMMARY]
+    Structure           : {}
+    Arms                : {}
+    naive_maxgap        : {}
+    fast_maxgap         : {}
+    reachability_graph  : {}
+    CSAR                : {}",
+                factory.name,
+                arm_num,
+                format_average(&naive_times),
+                format_average(&fast_times),
+                format_average(&reachability_times),
+                format_average(&csar_times)
+            );
+            match settings.format {
+                OutputFormat::Text => writeln!(out, "{}", text)?,
+                OutputFormat::Csv | OutputFormat::Json => eprintln!("{}", text),
+            }
+        }
+    }
+
+    if settings.format == OutputFormat::Json {
+        let document = json!({
+            "seed": settings.seed,
+            "samples_per_phase": settings.samples_per_phase,
+            "algorithm": algorithm.algorithm_type().key(),
+            "estimator": algorithm.estimator_key(),
+            "records": json_records,
+        });
+        serde_json::to_writer_pretty(&mut *out, &document)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Check that `fast_maxgap` agrees with `naive_maxgap`,
+/// and that `optimal` and `naive_gaps` agree with the brute force on small instances.
+/// Return whether they agree in every trial.
+pub fn verify(settings: &Settings) -> io::Result<bool> {
+    let results = maxgap_trials(settings, |structure, weights| {
+        Ok((
+            structure.compare_maxgap(weights)?,
+            structure.check_brute_force(weights),
+        ))
+    })?
+    .1;
+
+    let mut mismatch_count = 0;
+    let mut brute_force_count = 0;
+    let mut brute_force_mismatch_count = 0;
+    for (trial, (((naive_arm, _), (fast_arm, _)), brute_force_result)) in
+        results.into_iter().enumerate()
+    {
+        let trial_seed = derive_seed(settings.seed, trial as u64);
+        if naive_arm != fast_arm {
+            eprintln!(
+                "[RESULT] Mismatch. naive_maxgap = {}, fast_maxgap = {} (trial #{}, trial seed {})",
+                naive_arm, fast_arm, trial, trial_seed
+            );
+            mismatch_count += 1;
+        }
+        match brute_force_result {
+            Ok(checked) => brute_force_count += usize::from(checked),
+            Err(message) => {
+                eprintln!(
+                    "[RESULT] Mismatch. {} (trial #{}, trial seed {})",
+                    message, trial, trial_seed
+                );
+                brute_force_count += 1;
+                brute_force_mismatch_count += 1;
+            }
+        }
+    }
+
+    println!(
+        r"[SUMMARY]
+    Structure           : {}
+    Mismatches          : {}/{}
+    Brute force         : {}/{}",
+        settings.structure.name,
+        mismatch_count,
+        settings.trials,
+        brute_force_mismatch_count,
+        brute_force_count
+    );
+
+    Ok(mismatch_count == 0 && brute_force_mismatch_count == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        bench,
+        registry::{find_structure, StructureParams},
+        run, run_trial, wilson_interval, AlgorithmType, BenchSettings, EnumMeanEstimators,
+        OutputFormat, Settings, SourceSetting, Z_95,
+    };
+    use csar::{
+        algorithm::csar,
+        arms::{Arm, Arms, RewardFamily},
+        structure::uniform_matroid::UniformMatroid,
+        util::graph::reader::GraphFormat,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn thread_independence_test() {
+        let run_with_threads = |threads: usize| -> Vec<String> {
+            let settings = Settings {
+                structure: find_structure("uniform").unwrap(),
+                structure_params: StructureParams::default(),
+                graph: None,
+                source: SourceSetting::Synthetic(RewardFamily::Bernoulli),
+                arm_num: 20,
+                gap_profile: None,
+                algorithm: AlgorithmType::Csar,
+                estimators: EnumMeanEstimators::all(),
+                samples_per_phase: 10,
+                trials: 6,
+                seed: 0,
+                format: OutputFormat::Csv,
+                threads,
+            };
+            let mut out = Vec::new();
+            run(&settings, &mut out).unwrap();
+
+            // Drop the elapsed time and the CPU time.
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let mut columns: Vec<&str> = line.split(',').collect();
+                    columns.drain(7..9);
+                    columns.join(",")
+                })
+                .collect()
+        };
+
+        let sequential = run_with_threads(1);
+        assert_eq!(sequential.len(), 1 + 6 * 4);
+        assert_eq!(sequential, run_with_threads(4));
+    }
+
+    #[test]
+    fn early_decision_test() {
+        let run_with = |family: RewardFamily, estimators: Vec<EnumMeanEstimators>| {
+            let settings = Settings {
+                structure: find_structure("circuit").unwrap(),
+                structure_params: StructureParams::default(),
+                graph: None,
+                source: SourceSetting::Synthetic(family),
+                arm_num: 20,
+                gap_profile: None,
+                algorithm: AlgorithmType::EarlyDecision,
+                estimators,
+                samples_per_phase: 10,
+                trials: 2,
+                seed: 0,
+                format: OutputFormat::Csv,
+                threads: 1,
+            };
+            let mut out = Vec::new();
+            run(&settings, &mut out).map(|_| String::from_utf8(out).unwrap())
+        };
+        let empirical = || vec![EnumMeanEstimators::all().swap_remove(0)];
+
+        let out = run_with(RewardFamily::Bernoulli, empirical()).unwrap();
+        assert_eq!(out.lines().count(), 1 + 2);
+        assert!(out
+            .lines()
+            .skip(1)
+            .all(|line| line.contains(",early,empirical,")));
+
+        // There is no confidence bound for the heavy-tailed arms.
+        assert!(run_with(RewardFamily::Pareto, empirical()).is_err());
+        assert!(run_with(RewardFamily::Bernoulli, EnumMeanEstimators::all()).is_err());
+    }
+
+    #[test]
+    fn bench_test() {
+        let settings = Settings {
+            structure: find_structure("circuit").unwrap(),
+            structure_params: StructureParams::default(),
+            graph: None,
+            source: SourceSetting::Synthetic(RewardFamily::Gaussian),
+            arm_num: 100,
+            gap_profile: None,
+            algorithm: AlgorithmType::Csar,
+            estimators: vec![EnumMeanEstimators::all().swap_remove(0)],
+            samples_per_phase: 10,
+            trials: 2,
+            seed: 0,
+            format: OutputFormat::Csv,
+            threads: 1,
+        };
+        let bench_settings = BenchSettings {
+            sizes: vec![10, 30],
+            structures: vec![
+                find_structure("uniform").unwrap(),
+                find_structure("grid").unwrap(),
+            ],
+            naive_limit: 30,
+            csar_limit: 10,
+        };
+        let mut out = Vec::new();
+        bench(&settings, &bench_settings, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let rows: Vec<Vec<&str>> = out
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').collect())
+            .collect();
+        assert_eq!(rows.len(), 2 * 2 * 2);
+        for row in rows {
+            let arm_num: usize = row[3].parse().unwrap();
+            assert_eq!(row[6].is_empty(), arm_num > 30);
+            assert!(!row[7].is_empty() && !row[8].is_empty());
+            assert_eq!(row[9].is_empty(), arm_num > 10);
+        }
+
+        // Only one estimator and one structure can be measured.
+        let all_estimators = Settings {
+            estimators: EnumMeanEstimators::all(),
+            ..settings.clone()
+        };
+        assert!(bench(&all_estimators, &bench_settings, &mut Vec::new()).is_err());
+        let graph = Settings {
+            graph: Some((PathBuf::from("net.mtx"), GraphFormat::MatrixMarket)),
+            ..settings
+        };
+        assert!(bench(&graph, &bench_settings, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn zero_weight_test() {
+        // Every basis has the weight 0.
+        let structure = UniformMatroid::new(6, 3);
+        let mut arms = Arms::new(0);
+        for _ in 0..6 {
+            arms.push_arm(Arm::gaussian(0.0, 0.1).unwrap());
+        }
+        let result =
+            run_trial(&structure, &mut arms, |structure, env| csar(structure, env)).unwrap();
+        assert_eq!(result.simple_regret, 0.0);
+        assert_eq!(result.relative_error, 0.0);
+        assert!(result.correct);
+    }
+
+    #[test]
+    fn negative_weight_test() {
+        let structure = UniformMatroid::new(6, 3);
+        let mut arms = Arms::new(0);
+        for mean in [-1.0, -1.1, -1.2, -2.0, -2.1, -2.2] {
+            arms.push_arm(Arm::gaussian(mean, 0.1).unwrap());
+        }
+
+        // A wrong superarm has a positive regret and relative error.
+        let result = run_trial(&structure, &mut arms, |_, _| Ok(vec![3, 4, 5])).unwrap();
+        assert!((result.simple_regret - 3.0).abs() < 1e-12);
+        assert!((result.relative_error - 3.0 / 3.3).abs() < 1e-12);
+        assert!(!result.correct);
+
+        let result = run_trial(&structure, &mut arms, |_, _| Ok(vec![2, 1, 0])).unwrap();
+        assert!(result.simple_regret.abs() < 1e-12);
+        assert!(result.correct);
+    }
+
+    #[test]
+    fn wilson_interval_test() {
+        let (low, high) = wilson_interval(50, 100, Z_95);
+        assert!((low - 0.4038).abs() < 1e-4);
+        assert!((high - 0.5962).abs() < 1e-4);
+
+        // The interval stays in [0, 1] even if every trial is correct.
+        let (low, high) = wilson_interval(10, 10, Z_95);
+        assert!(0.0 < low && low < 1.0);
+        assert!((high - 1.0).abs() < 1e-12);
+
+        assert_eq!(wilson_interval(0, 0, Z_95), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,404 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use super::{
+    check_brute_force, compare_maxgap, dump_dot, run_trial_on, time_maxgap, Algorithm, BenchTimes,
+    DumpTarget, MaxgapComparison, TrialResult,
+};
+use csar::{
+    arms::BanditEnvironment,
+    error::Error,
+    hardness::generator::GapProfile,
+    structure::{
+        circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid, CombinatorialStructure,
+        RandomSample,
+    },
+    util::graph::{generator, Graph},
+};
+use rand::rngs::StdRng;
+
+/// The parameters of a structure given as `key=value` pairs.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct StructureParams(BTreeMap<String, String>);
+
+impl StructureParams {
+    /// Parse `key=value` pairs separated by commas.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut params = BTreeMap::new();
+        for pair in text
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("The parameter {:?} is not of the form key=value.", pair))?;
+            params.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(StructureParams(params))
+    }
+
+    /// Get the value of the parameter if given.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        self.0
+            .get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid value {:?} for the parameter {}.", value, key))
+            })
+            .transpose()
+    }
+
+    /// Check that every given parameter is accepted by the factory.
+    fn check_keys(&self, factory: &StructureFactory) -> Result<(), String> {
+        match self
+            .0
+            .keys()
+            .find(|key| factory.params.iter().all(|(accepted, _)| accepted != key))
+        {
+            Some(key) => Err(format!("{} has no parameter {:?}.", factory.name, key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An instance of a registered structure, whose concrete type is hidden from the runner.
+pub trait Instance: Sync {
+    /// The name of the concrete type of the structure.
+    fn type_name(&self) -> &'static str;
+
+    /// The parameters characterizing the instance, including the number of arms.
+    fn params(&self) -> &[(&'static str, usize)];
+
+    /// Get the number of arms, which may differ from the requested one for the graphs of fixed shapes.
+    fn get_arm_num(&self) -> usize {
+        self.params()[0].1
+    }
+
+    /// Run the algorithm once against the environment `env`.
+    fn run_trial(
+        &self,
+        algorithm: &Algorithm,
+        samples_per_phase: usize,
+        env: &mut dyn BanditEnvironment,
+    ) -> Result<TrialResult, Error>;
+
+    /// Run `naive_maxgap` and `fast_maxgap` with `weights`.
+    fn compare_maxgap(&self, weights: &[f64]) -> Result<MaxgapComparison, Error>;
+
+    /// Measure the elapsed time of `naive_maxgap` if `naive`, `fast_maxgap` and `reachability_graph`.
+    fn time_maxgap(&self, weights: &[f64], naive: bool) -> Result<BenchTimes, Error>;
+
+    /// Generate the means of the arms targeting the gaps of `profile`.
+    fn profile_means(&self, profile: &GapProfile, rng: &mut StdRng) -> Result<Vec<f64>, Error>;
+
+    /// Check `optimal` and `naive_gaps` with `weights` against the brute force if the instance is small.
+    fn check_brute_force(&self, weights: &[f64]) -> Result<bool, String>;
+
+    /// Draw the graph in the DOT language if any.
+    fn to_dot(&self, weights: &[f64], target: DumpTarget) -> Result<Option<String>, Error>;
+}
+
+struct Registered<Structure> {
+    structure: Structure,
+    params: Vec<(&'static str, usize)>,
+}
+
+impl<Structure: CombinatorialStructure + Sync + 'static> Registered<Structure> {
+    fn boxed(structure: Structure, params: &[(&'static str, usize)]) -> Box<dyn Instance> {
+        let mut all_params = vec![("arms", structure.get_arm_num())];
+        all_params.extend_from_slice(params);
+        Box::new(Registered {
+            structure,
+            params: all_params,
+        })
+    }
+}
+
+impl<Structure: CombinatorialStructure + Sync + 'static> Instance for Registered<Structure> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Structure>()
+    }
+
+    fn params(&self) -> &[(&'static str, usize)] {
+        &self.params
+    }
+
+    fn run_trial(
+        &self,
+        algorithm: &Algorithm,
+        samples_per_phase: usize,
+        mut env: &mut dyn BanditEnvironment,
+    ) -> Result<TrialResult, Error> {
+        run_trial_on(&self.structure, algorithm, samples_per_phase, &mut env)
+    }
+
+    fn compare_maxgap(&self, weights: &[f64]) -> Result<MaxgapComparison, Error> {
+        compare_maxgap(&self.structure, weights)
+    }
+
+    fn time_maxgap(&self, weights: &[f64], naive: bool) -> Result<BenchTimes, Error> {
+        time_maxgap(&self.structure, weights, naive)
+    }
+
+    fn profile_means(&self, profile: &GapProfile, rng: &mut StdRng) -> Result<Vec<f64>, Error> {
+        profile.means(&self.structure, rng)
+    }
+
+    fn check_brute_force(&self, weights: &[f64]) -> Result<bool, String> {
+        check_brute_force(&self.structure, weights)
+    }
+
+    fn to_dot(&self, weights: &[f64], target: DumpTarget) -> Result<Option<String>, Error> {
+        dump_dot(&self.structure, weights, target)
+    }
+}
+
+/// Build an instance with the number of arms and the parameters.
+type BuildFn = fn(usize, &StructureParams, &mut StdRng) -> Result<Box<dyn Instance>, String>;
+
+/// A way to build instances of a combinatorial structure.
+pub struct StructureFactory {
+    /// The name used in the command-line arguments and config files.
+    pub key: &'static str,
+    /// The name shown to humans.
+    pub name: &'static str,
+    /// The accepted parameters and their descriptions.
+    /// The parameters not given are chosen randomly.
+    pub params: &'static [(&'static str, &'static str)],
+    build: BuildFn,
+}
+
+impl StructureFactory {
+    /// Build an instance with `arm_num` arms.
+    pub fn build(
+        &self,
+        arm_num: usize,
+        params: &StructureParams,
+        rng: &mut StdRng,
+    ) -> Result<Box<dyn Instance>, String> {
+        params.check_keys(self)?;
+        (self.build)(arm_num, params, rng)
+    }
+}
+
+/// Every structure available in experiments.
+/// A new structure has only to be added here.
+///
+/// The random graphs are connected and have exactly the requested number of edges,
+/// while the grid, torus and complete graphs have the number closest to it.
+pub static STRUCTURES: &[StructureFactory] = &[
+    StructureFactory {
+        key: "uniform",
+        name: "Uniform Matroid",
+        params: &[("rank", "The rank of the matroid")],
+        build: |arm_num, params, rng| {
+            let structure = match params.get("rank")? {
+                Some(rank) if rank > arm_num => {
+                    return Err(format!("The rank {} exceeds the number of arms.", rank))
+                }
+                Some(rank) => UniformMatroid::new(arm_num, rank),
+                None => UniformMatroid::sample(arm_num, rng),
+            };
+            let rank = structure.get_rank();
+            Ok(Registered::boxed(structure, &[("rank", rank)]))
+        },
+    },
+    StructureFactory {
+        key: "circuit",
+        name: "Circuit Matroid",
+        params: &[(
+            "vertices",
+            "The number of vertices of the random graph G(n, m)",
+        )],
+        build: |arm_num, params, rng| {
+            let vnum = get_vnum(arm_num, params, rng)?;
+            Ok(circuit_instance(&generator::gnm(vnum, arm_num, rng)))
+        },
+    },
+    StructureFactory {
+        key: "geometric",
+        name: "Circuit Matroid of a random geometric graph",
+        params: &[("vertices", "The number of random points in the unit square")],
+        build: |arm_num, params, rng| {
+            let vnum = get_vnum(arm_num, params, rng)?;
+            Ok(circuit_instance(&generator::random_geometric(
+                vnum, arm_num, rng,
+            )))
+        },
+    },
+    StructureFactory {
+        key: "grid",
+        name: "Circuit Matroid of a grid graph",
+        params: &[(
+            "rows",
+            "The number of rows, with the columns fitted to the arms",
+        )],
+        build: |arm_num, params, _| {
+            let rows = params
+                .get("rows")?
+                .unwrap_or_else(|| ((arm_num as f64 / 2.0).sqrt().round() as usize).max(1));
+            if rows == 0 {
+                return Err("A grid needs at least one row.".to_string());
+            }
+            // A grid has `2 * rows * cols - rows - cols` edges.
+            let cols = ((arm_num + rows) as f64 / (2 * rows - 1) as f64).round() as usize;
+            Ok(circuit_instance(&generator::grid(rows, cols.max(1))))
+        },
+    },
+    StructureFactory {
+        key: "torus",
+        name: "Circuit Matroid of a torus graph",
+        params: &[(
+            "rows",
+            "The number of rows, with the columns fitted to the arms",
+        )],
+        build: |arm_num, params, _| {
+            let rows = params
+                .get("rows")?
+                .unwrap_or_else(|| ((arm_num as f64 / 2.0).sqrt().round() as usize).max(3));
+            if rows < 3 {
+                return Err("A torus needs at least three rows.".to_string());
+            }
+            // A torus has `2 * rows * cols` edges.
+            let cols = (arm_num as f64 / (2 * rows) as f64).round() as usize;
+            Ok(circuit_instance(&generator::torus(rows, cols.max(3))))
+        },
+    },
+    StructureFactory {
+        key: "barabasi-albert",
+        name: "Circuit Matroid of a Barabási–Albert graph",
+        params: &[(
+            "degree",
+            "The number of edges of each new vertex [default: 2]",
+        )],
+        build: |arm_num, params, rng| {
+            let degree = params.get("degree")?.unwrap_or(2);
+            if degree == 0 {
+                return Err("The degree must be positive.".to_string());
+            }
+            Ok(circuit_instance(&generator::barabasi_albert(
+                arm_num, degree, rng,
+            )))
+        },
+    },
+    StructureFactory {
+        key: "complete",
+        name: "Circuit Matroid of a complete graph",
+        params: &[],
+        build: |arm_num, _, _| {
+            // The complete graph with the number of edges closest to the arms.
+            let vnum = ((1.0 + (1.0 + 8.0 * arm_num as f64).sqrt()) / 2.0).round() as usize;
+            Ok(circuit_instance(&generator::complete(vnum.max(2))))
+        },
+    },
+    StructureFactory {
+        key: "sparse",
+        name: "Circuit Matroid of a random tree",
+        params: &[],
+        build: |arm_num, _, rng| Ok(circuit_instance(&generator::gnm(arm_num + 1, arm_num, rng))),
+    },
+    StructureFactory {
+        key: "dense",
+        name: "Circuit Matroid of a nearly complete random graph",
+        params: &[],
+        build: |arm_num, _, rng| {
+            // The fewest vertices with enough pairs.
+            let mut vnum = 1;
+            while vnum * (vnum - 1) / 2 < arm_num {
+                vnum += 1;
+            }
+            Ok(circuit_instance(&generator::gnm(vnum, arm_num, rng)))
+        },
+    },
+];
+
+/// The number of vertices given by the parameter, or chosen randomly for `arm_num` edges.
+fn get_vnum(arm_num: usize, params: &StructureParams, rng: &mut StdRng) -> Result<usize, String> {
+    let vnum = match params.get("vertices")? {
+        Some(vnum) => vnum,
+        None => CircuitMatroid::sample_vnum(arm_num, rng),
+    };
+    generator::check_size(vnum, arm_num)?;
+    Ok(vnum)
+}
+
+/// The circuit matroid of `graph`.
+pub fn circuit_instance(graph: &Graph) -> Box<dyn Instance> {
+    let structure = CircuitMatroid::new(graph);
+    let vnum = structure.get_vnum();
+    Registered::boxed(structure, &[("vertices", vnum)])
+}
+
+/// Find the structure by its key.
+pub fn find_structure(key: &str) -> Option<&'static StructureFactory> {
+    STRUCTURES.iter().find(|factory| factory.key == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_structure, StructureParams, STRUCTURES};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn registry_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // The menu lists the structures in this order.
+        let expected = [
+            ("uniform", "UniformMatroid"),
+            ("circuit", "CircuitMatroid"),
+            ("geometric", "CircuitMatroid"),
+            ("grid", "CircuitMatroid"),
+            ("torus", "CircuitMatroid"),
+            ("barabasi-albert", "CircuitMatroid"),
+            ("complete", "CircuitMatroid"),
+            ("sparse", "CircuitMatroid"),
+            ("dense", "CircuitMatroid"),
+        ];
+        assert_eq!(STRUCTURES.len(), expected.len());
+        for (factory, (key, type_name)) in STRUCTURES.iter().zip(expected) {
+            assert_eq!(factory.key, key);
+            assert!(std::ptr::eq(find_structure(key).unwrap(), factory));
+
+            let instance = factory
+                .build(50, &StructureParams::default(), &mut rng)
+                .unwrap();
+            assert!(
+                instance.type_name().ends_with(type_name),
+                "{} builds {}.",
+                key,
+                instance.type_name()
+            );
+            assert_eq!(instance.params()[0].0, "arms");
+            if ["grid", "torus", "complete"].contains(&key) {
+                assert!(instance.get_arm_num().abs_diff(50) <= 5);
+            } else {
+                assert_eq!(instance.get_arm_num(), 50);
+            }
+        }
+    }
+
+    #[test]
+    fn params_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let uniform = find_structure("uniform").unwrap();
+        let circuit = find_structure("circuit").unwrap();
+
+        let params = StructureParams::parse("rank=5").unwrap();
+        let instance = uniform.build(20, &params, &mut rng).unwrap();
+        assert_eq!(instance.params(), &[("arms", 20), ("rank", 5)]);
+
+        let params = StructureParams::parse(" vertices = 8 ").unwrap();
+        let instance = circuit.build(20, &params, &mut rng).unwrap();
+        assert_eq!(instance.params(), &[("arms", 20), ("vertices", 8)]);
+
+        let params = StructureParams::parse("rank=21").unwrap();
+        assert!(uniform.build(20, &params, &mut rng).is_err());
+        let params = StructureParams::parse("vertices=5").unwrap();
+        assert!(circuit.build(20, &params, &mut rng).is_err());
+        let params = StructureParams::parse("rank=5").unwrap();
+        assert!(circuit.build(20, &params, &mut rng).is_err());
+        assert!(StructureParams::parse("rank").is_err());
+    }
+}
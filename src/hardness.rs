@@ -0,0 +1,89 @@
+pub mod generator;
+
+use crate::{error::Error, structure::CombinatorialStructure};
+
+/// The complexity measures of an instance defined by the gaps of the arms,
+/// following Chen et al., "Combinatorial Pure Exploration of Multi-Armed Bandits" (NIPS 2014).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hardness {
+    /// H1 = Σ_e Δ_e^-2.
+    pub h1: f64,
+    /// H2 = max_i i / Δ_(i)^2.
+    pub h2: f64,
+    /// The width of the structure.
+    pub width: usize,
+}
+
+impl Hardness {
+    /// Compute the measures of `structure` whose arms have the true means `weights`.
+    /// Return an error in the same cases as `all_gaps`.
+    pub fn new(structure: &impl CombinatorialStructure, weights: &[f64]) -> Result<Self, Error> {
+        let gaps = structure.all_gaps(weights)?;
+        Ok(Hardness {
+            h1: h1(&gaps),
+            h2: h2(&gaps),
+            width: structure.width(),
+        })
+    }
+
+    /// The hardness width^2 H1 in the sample complexity of CLUCB.
+    pub fn clucb(&self) -> f64 {
+        (self.width * self.width) as f64 * self.h1
+    }
+
+    /// The hardness width^2 H2 in the error probability of CSAR.
+    pub fn csar(&self) -> f64 {
+        (self.width * self.width) as f64 * self.h2
+    }
+}
+
+/// Compute the hardness H1 = Σ_e Δ_e^-2 of the instance.
+/// The NaN gaps of the removed arms are ignored, and the infinite gaps contribute nothing.
+pub fn h1(gaps: &[f64]) -> f64 {
+    gaps.iter()
+        .filter(|gap| !gap.is_nan())
+        .map(|gap| 1.0 / (gap * gap))
+        .sum()
+}
+
+/// Compute the hardness H2 = max_i i / Δ_(i)^2 of the instance,
+/// where Δ_(1) ≤ Δ_(2) ≤ ... are the gaps of the arms in increasing order.
+/// The NaN gaps of the removed arms are ignored, and the infinite gaps contribute nothing.
+pub fn h2(gaps: &[f64]) -> f64 {
+    let mut gaps: Vec<f64> = gaps.iter().copied().filter(|gap| !gap.is_nan()).collect();
+    gaps.sort_unstable_by(f64::total_cmp);
+
+    gaps.iter()
+        .enumerate()
+        .map(|(i, gap)| (i + 1) as f64 / (gap * gap))
+        .fold(0_f64, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{h1, h2, Hardness};
+    use crate::structure::{uniform_matroid::UniformMatroid, CombinatorialStructure};
+
+    #[test]
+    fn h1_h2_test() {
+        assert_eq!(h1(&[f64::NAN, 1.0, 0.5, f64::INFINITY]), 5.0);
+        assert_eq!(h2(&[f64::NAN, 1.0, 0.5, f64::INFINITY]), 4.0);
+        assert_eq!(h2(&[0.5, 0.5, 0.5]), 12.0);
+        assert_eq!(h2(&[1.0, 0.0]), f64::INFINITY);
+        assert_eq!(h1(&[f64::NAN]), 0.0);
+        assert_eq!(h2(&[f64::NAN]), 0.0);
+    }
+
+    #[test]
+    fn hardness_test() {
+        // The gaps of the arms 0, 1, 2 and 3 are 0.5, 0.5, 1 and 1.
+        let mut structure = UniformMatroid::new(5, 2);
+        structure.delete_arm(4).unwrap();
+        let hardness = Hardness::new(&structure, &[1.0, 0.5, 0.0, 1.5, 0.0]).unwrap();
+        assert_eq!(hardness.h1, 10.0);
+        assert_eq!(hardness.h2, 8.0);
+        assert_eq!(hardness.width, 2);
+        assert_eq!(hardness.clucb(), 40.0);
+        assert_eq!(hardness.csar(), 32.0);
+    }
+}
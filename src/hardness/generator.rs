@@ -0,0 +1,359 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{error::Error, structure::CombinatorialStructure};
+
+/// The gaps targeted by the generated means.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum GapProfile {
+    /// Every arm has the gap `gap`.
+    Equal { gap: f64 },
+    /// The i-th largest target is `gap * i^-exponent`.
+    Polynomial { gap: f64, exponent: f64 },
+    /// The i-th largest target is `gap * ratio^(i - 1)`.
+    Geometric { gap: f64, ratio: f64 },
+    /// An arm in the optimal basis and an arm exchangeable with it have the gap `tie`,
+    /// and the other arms target `gap`.
+    NearTie { gap: f64, tie: f64 },
+}
+
+/// Return the error with `reason` unless `condition` holds.
+fn require(condition: bool, reason: &str) -> Result<(), Error> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::InvalidGapProfile {
+            reason: reason.to_string(),
+        })
+    }
+}
+
+impl GapProfile {
+    /// Check that the targeted gaps are in (0, 1/2], which keeps the means in [0, 1].
+    fn check(&self) -> Result<(), Error> {
+        match *self {
+            GapProfile::Equal { gap } => {
+                require(0.0 < gap && gap <= 0.5, "0 < gap <= 1/2 is required")
+            }
+            GapProfile::Polynomial { gap, exponent } => {
+                require(0.0 < gap && gap <= 0.5, "0 < gap <= 1/2 is required")?;
+                require(exponent > 0.0, "exponent > 0 is required")
+            }
+            GapProfile::Geometric { gap, ratio } => {
+                require(0.0 < gap && gap <= 0.5, "0 < gap <= 1/2 is required")?;
+                require(0.0 < ratio && ratio < 1.0, "0 < ratio < 1 is required")
+            }
+            GapProfile::NearTie { gap, tie } => {
+                require(0.0 < gap && gap <= 0.5, "0 < gap <= 1/2 is required")?;
+                require(0.0 < tie && tie <= gap, "0 < tie <= gap is required")
+            }
+        }
+    }
+
+    /// Generate the means of the arms of `structure` targeting the gaps of this profile.
+    ///
+    /// A random basis becomes the optimal one, and the targets are assigned to the arms at random
+    /// except that the boundary pair, an arm `e` in the basis and an arm `f` exchangeable with it,
+    /// both take the smallest target δ.
+    /// With the means 1/2 - δ/2 + Δ for the arms in the basis and 1/2 + δ/2 - Δ for the others,
+    /// the gap of an arm in the basis exchangeable with `f`, or of an arm out of it exchangeable with `e`,
+    /// is exactly its target Δ,
+    /// which holds for every arm of a uniform matroid,
+    /// while the gap of any other arm is Δ plus the smallest target exchangeable with it minus δ.
+    /// The realized profile thus repeats δ in place of the second smallest target,
+    /// the equal gaps are exact for every arm, and the arms exchangeable with neither arm of a near tie
+    /// have the gap `2 gap - tie`.
+    /// The arms exchangeable with no arm have the infinite gaps, and the removed arms have the mean 1/2.
+    /// Return an error if the parameters are out of range, if the smallest target is too small
+    /// to separate the means of the boundary pair, or if there is no basis.
+    pub fn means(
+        &self,
+        structure: &impl CombinatorialStructure,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<f64>, Error> {
+        self.check()?;
+        let arm_num = structure.get_arm_num();
+        let mut arms = structure.get_arms();
+        arms.shuffle(rng);
+
+        let random_weights: Vec<f64> = (0..arm_num).map(|_| rng.gen()).collect();
+        let basis = structure.optimal(&random_weights).ok_or(Error::NoBasis)?;
+        let mut in_basis = vec![false; arm_num];
+        for &i in &basis {
+            in_basis[i] = true;
+        }
+
+        // Move the boundary pair to the end, where the targets are the smallest.
+        let pair = exchangeable_pair(structure, &basis, &in_basis, rng);
+        if let Some((e, f)) = pair {
+            let last = arms.len() - 1;
+            let position = arms.iter().position(|&i| i == e).unwrap();
+            arms.swap(position, last);
+            let position = arms.iter().position(|&i| i == f).unwrap();
+            arms.swap(position, last - 1);
+        }
+
+        let mut targets = vec![0_f64; arm_num];
+        for (k, &i) in arms.iter().enumerate() {
+            targets[i] = match *self {
+                GapProfile::Equal { gap } | GapProfile::NearTie { gap, .. } => gap,
+                GapProfile::Polynomial { gap, exponent } => gap * ((k + 1) as f64).powf(-exponent),
+                GapProfile::Geometric { gap, ratio } => gap * ratio.powi(k as i32),
+            };
+        }
+        let mut boundary = 0.0;
+        if let Some((e, f)) = pair {
+            boundary = match *self {
+                GapProfile::NearTie { tie, .. } => tie,
+                _ => targets[e],
+            };
+            targets[e] = boundary;
+            targets[f] = boundary;
+            // The decaying targets may underflow for many arms.
+            require(
+                0.5 + boundary / 2.0 > 0.5,
+                "the smallest target vanishes in the means",
+            )?;
+        }
+
+        let mut means = vec![0.5; arm_num];
+        for &i in &arms {
+            means[i] = if in_basis[i] {
+                0.5 - boundary / 2.0 + targets[i]
+            } else {
+                0.5 + boundary / 2.0 - targets[i]
+            };
+        }
+        Ok(means)
+    }
+}
+
+/// Find a random arm `e` in `basis` and an arm `f` not in it such that `basis - e + f` is a basis,
+/// i.e. there is a path from `e` to `f` in the reachability graph.
+fn exchangeable_pair(
+    structure: &impl CombinatorialStructure,
+    basis: &[usize],
+    in_basis: &[bool],
+    rng: &mut impl Rng,
+) -> Option<(usize, usize)> {
+    let arm_num = structure.get_arm_num();
+    let graph = structure.reachability_graph(basis);
+    let mut adj = vec![Vec::new(); graph.get_vnum()];
+    for (u, v) in graph.get_edges() {
+        adj[u].push(v);
+    }
+
+    let mut candidates = basis.to_vec();
+    candidates.shuffle(rng);
+    for e in candidates {
+        // Search the arms not in the basis reachable from e.
+        let mut visited = vec![false; graph.get_vnum()];
+        let mut stack = vec![e];
+        visited[e] = true;
+        let mut reachable = Vec::new();
+        while let Some(u) = stack.pop() {
+            if u < arm_num && !in_basis[u] {
+                reachable.push(u);
+            }
+            for &v in &adj[u] {
+                if !visited[v] {
+                    visited[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+        if let Some(&f) = reachable.choose(rng) {
+            return Some((e, f));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GapProfile;
+    use crate::{
+        algorithm::brute_force::enumerate_bases,
+        error::Error,
+        structure::{
+            circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid,
+            CombinatorialStructure,
+        },
+        util::graph::generator,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Sort the finite gaps in decreasing order.
+    fn sorted_gaps(structure: &impl CombinatorialStructure, means: &[f64]) -> Vec<f64> {
+        let mut gaps: Vec<f64> = structure
+            .all_gaps(means)
+            .unwrap()
+            .into_iter()
+            .filter(|gap| gap.is_finite())
+            .collect();
+        gaps.sort_unstable_by(|a, b| b.total_cmp(a));
+        gaps
+    }
+
+    #[test]
+    fn equal_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let structure = CircuitMatroid::new(&generator::grid(4, 5));
+        let means = GapProfile::Equal { gap: 0.2 }
+            .means(&structure, &mut rng)
+            .unwrap();
+        let gaps = sorted_gaps(&structure, &means);
+        assert_eq!(gaps.len(), structure.get_arm_num());
+        assert!(gaps.iter().all(|gap| (gap - 0.2).abs() < 1e-12));
+    }
+
+    /// Return whether exchanging `e` and `f` turns `basis` into another basis of `structure`.
+    fn exchange_relation(
+        structure: &CircuitMatroid,
+        basis: &[usize],
+    ) -> impl Fn(usize, usize) -> bool {
+        let bases = enumerate_bases(structure).unwrap();
+        let basis = basis.to_vec();
+        move |e, f| {
+            let (e, f) = if basis.contains(&e) { (e, f) } else { (f, e) };
+            let mut exchanged: Vec<usize> = basis.iter().copied().filter(|&i| i != e).collect();
+            exchanged.push(f);
+            exchanged.sort_unstable();
+            basis.contains(&e) && !basis.contains(&f) && bases.contains(&exchanged)
+        }
+    }
+
+    #[test]
+    fn near_tie_test() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let profile = GapProfile::NearTie {
+            gap: 0.5,
+            tie: 0.01,
+        };
+
+        // Every arm is exchangeable with the tied pair in a uniform matroid.
+        let structure = UniformMatroid::new(10, 4);
+        let means = profile.means(&structure, &mut rng).unwrap();
+        let gaps = sorted_gaps(&structure, &means);
+        assert!(gaps[..8].iter().all(|gap| (gap - 0.5).abs() < 1e-12));
+        assert!(gaps[8..].iter().all(|gap| (gap - 0.01).abs() < 1e-12));
+
+        // Check the gap of every arm by the exchanges between the bases.
+        let structure = CircuitMatroid::new(&generator::grid(3, 4));
+        let means = profile.means(&structure, &mut rng).unwrap();
+        let gaps = structure.all_gaps(&means).unwrap();
+        let exchangeable = exchange_relation(&structure, &structure.optimal(&means).unwrap());
+
+        let tied: Vec<usize> = (0..gaps.len())
+            .filter(|&i| (gaps[i] - 0.01).abs() < 1e-12)
+            .collect();
+        assert_eq!(tied.len(), 2);
+        assert!(exchangeable(tied[0], tied[1]));
+        for (i, gap) in gaps.into_iter().enumerate() {
+            let expected = if tied.contains(&i) {
+                0.01
+            } else if tied.iter().any(|&t| exchangeable(i, t)) {
+                0.5
+            } else {
+                2.0 * 0.5 - 0.01
+            };
+            assert!((gap - expected).abs() < 1e-12, "arm {}: {}", i, gap);
+        }
+    }
+
+    #[test]
+    fn decay_test() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let profiles = [
+            GapProfile::Polynomial {
+                gap: 0.5,
+                exponent: 1.0,
+            },
+            GapProfile::Geometric {
+                gap: 0.5,
+                ratio: 0.7,
+            },
+        ];
+        // The targets in decreasing order, where the boundary pair takes the smallest one twice.
+        let profile_targets = |profile: &GapProfile, arm_num: usize| {
+            let mut targets: Vec<f64> = (0..arm_num)
+                .map(|k| match *profile {
+                    GapProfile::Polynomial { gap, exponent } => {
+                        gap * ((k + 1) as f64).powf(-exponent)
+                    }
+                    GapProfile::Geometric { gap, ratio } => gap * ratio.powi(k as i32),
+                    _ => unreachable!(),
+                })
+                .collect();
+            targets[arm_num - 2] = targets[arm_num - 1];
+            targets
+        };
+
+        // Every gap is exact in a uniform matroid.
+        let structure = UniformMatroid::new(20, 5);
+        for profile in &profiles {
+            let means = profile.means(&structure, &mut rng).unwrap();
+            let gaps = sorted_gaps(&structure, &means);
+            let targets = profile_targets(profile, 20);
+            for (gap, target) in gaps.iter().zip(&targets) {
+                assert!((gap - target).abs() < 1e-12, "{:?}: {:?}", profile, gaps);
+            }
+        }
+
+        // The gaps exchangeable with the boundary pair are exact, and the others are no smaller.
+        let structure = CircuitMatroid::new(&generator::grid(3, 4));
+        let arm_num = structure.get_arm_num();
+        for profile in &profiles {
+            let means = profile.means(&structure, &mut rng).unwrap();
+            let gaps = structure.all_gaps(&means).unwrap();
+            let exchangeable = exchange_relation(&structure, &structure.optimal(&means).unwrap());
+            let targets = profile_targets(profile, arm_num);
+            let smallest = targets[arm_num - 1];
+
+            let boundary: Vec<usize> = (0..arm_num)
+                .filter(|&i| (gaps[i] - smallest).abs() < 1e-12)
+                .collect();
+            assert_eq!(boundary.len(), 2);
+            for (i, &gap) in gaps.iter().enumerate() {
+                if boundary.iter().any(|&b| exchangeable(i, b)) {
+                    assert!(targets.iter().any(|target| (gap - target).abs() < 1e-12));
+                }
+            }
+            let gaps = sorted_gaps(&structure, &means);
+            for (gap, target) in gaps.iter().zip(&targets) {
+                assert!(*gap > target - 1e-12, "{:?}: {:?}", profile, gaps);
+            }
+        }
+
+        assert!(matches!(
+            GapProfile::Geometric {
+                gap: 0.5,
+                ratio: 1.0
+            }
+            .means(&structure, &mut rng),
+            Err(Error::InvalidGapProfile { .. })
+        ));
+        assert!(GapProfile::Equal { gap: 0.0 }
+            .means(&structure, &mut rng)
+            .is_err());
+        assert!(GapProfile::Equal { gap: 0.8 }
+            .means(&structure, &mut rng)
+            .is_err());
+        // The smallest targets underflow.
+        let structure = UniformMatroid::new(400, 100);
+        assert!(matches!(
+            GapProfile::Geometric {
+                gap: 0.5,
+                ratio: 0.1
+            }
+            .means(&structure, &mut rng),
+            Err(Error::InvalidGapProfile { .. })
+        ));
+        assert!(GapProfile::Polynomial {
+            gap: 0.5,
+            exponent: 1.0
+        }
+        .means(&structure, &mut rng)
+        .is_ok());
+    }
+}
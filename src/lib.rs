@@ -0,0 +1,18 @@
+//! Combinatorial successive accept reject (CSAR) for the combinatorial pure exploration
+//! of multi-armed bandits, with faster maxgap computations on matroids.
+//!
+//! * `structure` defines the combinatorial structures and their maxgap computations.
+//! * `algorithm` runs CSAR on a structure and an environment of `arms`.
+//! * `sampler` and `estimator` estimate the means of the arms from their rewards.
+//! * `hardness` measures how hard an instance is from the gaps of the arms.
+
+pub mod algorithm;
+pub mod arms;
+pub mod error;
+pub mod estimator;
+pub mod hardness;
+pub mod sampler;
+pub mod structure;
+pub mod util;
+
+pub use error::Error;
@@ -1,25 +1,169 @@
+#[derive(Clone)]
 pub struct Sampler {
-    trial: u32,
+    count: u64,
     mean: f64,
+    /// The sum of squared deviations from the mean.
+    m2: f64,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler::new()
+    }
 }
 
 impl Sampler {
     pub fn new() -> Self {
         Sampler {
-            trial: 0_u32,
+            count: 0_u64,
             mean: 0_f64,
+            m2: 0_f64,
         }
     }
 
-    /// Update the empirical mean by the observed value.
+    /// Update the empirical mean and variance by the observed value with Welford's algorithm.
     pub fn observe(&mut self, val: f64) -> &mut Self {
-        self.mean = (self.mean * (self.trial as f64) + val) / ((self.trial + 1) as f64);
-        self.trial += 1;
+        self.count += 1;
+        let delta = val - self.mean;
+        self.mean += delta / (self.count as f64);
+        self.m2 += delta * (val - self.mean);
         self
     }
 
+    /// Return the number of observed values.
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+
     /// Return the empirical mean.
     pub fn get_mean(&self) -> f64 {
         self.mean
     }
+
+    /// Return the unbiased sample variance.
+    /// It is 0 if fewer than 2 values are observed.
+    pub fn get_variance(&self) -> f64 {
+        if self.count < 2 {
+            0_f64
+        } else {
+            self.m2 / ((self.count - 1) as f64)
+        }
+    }
+
+    /// Return the standard error of the empirical mean.
+    pub fn get_std_error(&self) -> f64 {
+        if self.count == 0 {
+            f64::INFINITY
+        } else {
+            (self.get_variance() / (self.count as f64)).sqrt()
+        }
+    }
+
+    /// Return the radius `r` such that the true mean lies in `[mean - r, mean + r]`
+    /// with probability at least `1 - delta`, by Hoeffding's inequality.
+    /// It is required that the rewards are bounded in an interval of length `range`.
+    pub fn hoeffding_radius(&self, range: f64, delta: f64) -> f64 {
+        if self.count == 0 {
+            return f64::INFINITY;
+        }
+        range * ((2.0 / delta).ln() / (2.0 * self.count as f64)).sqrt()
+    }
+
+    /// Return the radius `r` such that the true mean lies in `[mean - r, mean + r]`
+    /// with probability at least `1 - delta`, by the empirical Bernstein bound of Maurer and Pontil.
+    /// It is required that the rewards are bounded in an interval of length `range`.
+    pub fn empirical_bernstein_radius(&self, range: f64, delta: f64) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.count as f64;
+        let log_term = (4.0 / delta).ln();
+        (2.0 * self.get_variance() * log_term / n).sqrt()
+            + 7.0 * range * log_term / (3.0 * (n - 1.0))
+    }
+
+    /// Return the radius `r` such that the true mean lies in `[mean - r, mean + r]`
+    /// with probability at least `1 - delta`.
+    /// It is required that the rewards are `sigma`-sub-Gaussian.
+    pub fn sub_gaussian_radius(&self, sigma: f64, delta: f64) -> f64 {
+        if self.count == 0 {
+            return f64::INFINITY;
+        }
+        sigma * (2.0 * (2.0 / delta).ln() / (self.count as f64)).sqrt()
+    }
+}
+
+/// The concentration inequality giving the confidence radius of the empirical mean.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum ConfidenceBound {
+    /// Hoeffding's inequality for the rewards bounded in an interval of length `range`.
+    Hoeffding { range: f64 },
+    /// The empirical Bernstein bound for the rewards bounded in an interval of length `range`.
+    EmpiricalBernstein { range: f64 },
+    /// The bound for the `sigma`-sub-Gaussian rewards.
+    SubGaussian { sigma: f64 },
+}
+
+impl ConfidenceBound {
+    /// Return the radius of `sampler` with probability at least `1 - delta`.
+    pub fn radius(&self, sampler: &Sampler, delta: f64) -> f64 {
+        match *self {
+            ConfidenceBound::Hoeffding { range } => sampler.hoeffding_radius(range, delta),
+            ConfidenceBound::EmpiricalBernstein { range } => {
+                sampler.empirical_bernstein_radius(range, delta)
+            }
+            ConfidenceBound::SubGaussian { sigma } => sampler.sub_gaussian_radius(sigma, delta),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sampler;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn welford_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // A large offset makes the naive one-pass formula unstable.
+        let values: Vec<f64> = (0..10_000).map(|_| 1e9 + rng.gen::<f64>()).collect();
+
+        let mut sampler = Sampler::new();
+        for &val in &values {
+            sampler.observe(val);
+        }
+
+        // The naive two-pass computation.
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+        assert_eq!(sampler.get_count(), values.len() as u64);
+        assert!((sampler.get_mean() - mean).abs() < 1e-6);
+        assert!((sampler.get_variance() - variance).abs() < 1e-6 * variance);
+        assert!((sampler.get_std_error() - (variance / n).sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn radius_test() {
+        let mut sampler = Sampler::new();
+        assert_eq!(sampler.hoeffding_radius(1.0, 0.05), f64::INFINITY);
+        assert_eq!(sampler.empirical_bernstein_radius(1.0, 0.05), f64::INFINITY);
+        assert_eq!(sampler.sub_gaussian_radius(1.0, 0.05), f64::INFINITY);
+
+        for i in 0..100 {
+            sampler.observe((i % 2) as f64);
+        }
+        let hoeffding = sampler.hoeffding_radius(1.0, 0.05);
+        assert!((hoeffding - (40_f64.ln() / 200.0).sqrt()).abs() < 1e-12);
+
+        // The radii shrink as more values are observed.
+        for i in 0..100 {
+            sampler.observe((i % 2) as f64);
+        }
+        assert!(sampler.hoeffding_radius(1.0, 0.05) < hoeffding);
+        assert!(sampler.empirical_bernstein_radius(1.0, 0.05).is_finite());
+        assert!(sampler.sub_gaussian_radius(0.5, 0.05) > sampler.sub_gaussian_radius(0.25, 0.05));
+    }
 }
@@ -1,8 +1,42 @@
 pub mod circuit_matroid;
 pub mod uniform_matroid;
 
-use crate::util::graph::Graph;
-use std::collections::VecDeque;
+use crate::{error::Error, util::graph::Graph};
+use rand::Rng;
+use std::{cmp::Ordering, collections::VecDeque};
+
+/// Check that the weights of `arms` are not NaN.
+pub fn check_weights(arms: &[usize], weights: &[f64]) -> Result<(), Error> {
+    match arms.iter().find(|&&i| weights[i].is_nan()) {
+        Some(&arm) => Err(Error::NanWeight { arm }),
+        None => Ok(()),
+    }
+}
+
+/// Find the position of the arm `i` in `arms`.
+pub fn find_arm(arms: &[usize], i: usize) -> Result<usize, Error> {
+    arms.iter()
+        .position(|&r| r == i)
+        .ok_or(Error::ArmNotFound { arm: i })
+}
+
+/// Compare the arms `i` and `j` in the canonical order,
+/// where the heavier arm comes first and the smaller index breaks ties.
+/// The weights are compared by `f64::total_cmp`, so the order is total even for NaN,
+/// after adding 0 to make -0 equal to +0.
+pub fn compare_arms(weights: &[f64], i: usize, j: usize) -> Ordering {
+    (weights[j] + 0.0)
+        .total_cmp(&(weights[i] + 0.0))
+        .then(i.cmp(&j))
+}
+
+/// Find the arm with the maximum gap among `arms`, where the smaller index breaks ties.
+pub fn maxgap_arm(arms: &[usize], gaps: &[f64]) -> Result<usize, Error> {
+    arms.iter()
+        .copied()
+        .min_by(|&i, &j| compare_arms(gaps, i, j))
+        .ok_or(Error::NoArm)
+}
 
 pub trait CombinatorialStructure: Clone {
     /// Get the number of arms.
@@ -12,31 +46,58 @@ pub trait CombinatorialStructure: Clone {
     fn get_arms(&self) -> Vec<usize>;
 
     /// Contract the arm i.
-    /// Assume that the arm i remains.
-    fn contract_arm(&mut self, i: usize) -> &mut Self;
+    /// Return an error if the arm i does not remain.
+    fn contract_arm(&mut self, i: usize) -> Result<&mut Self, Error>;
 
     /// Delete the arm i.
-    /// Assume that the arm i remains.
-    fn delete_arm(&mut self, i: usize) -> &mut Self;
+    /// Return an error if the arm i does not remain.
+    fn delete_arm(&mut self, i: usize) -> Result<&mut Self, Error>;
 
-    /// Find the superarm with the maximum sum of weights.
+    /// Find the superarm with the maximum sum of weights, or `None` if there is no superarm.
+    /// Ties are broken by the canonical order of `compare_arms`, so the superarm is deterministic.
+    /// It is required that the weights of the remaining arms are not NaN.
     fn optimal(&self, weights: &[f64]) -> Option<Vec<usize>>;
 
     /// Build a directed acyclic graph satisfying the following properties.
     ///
     /// * Every arm corresponds to a vertex in DAG.
     /// * There is a path from an arm *in* the basis to an arm *not in* the basis
-    ///     iff the former arm is in the fundamental circuit of the latter arm.
+    ///   iff the former arm is in the fundamental circuit of the latter arm.
     ///
     /// It is required that `basis` induces a basis.
     fn reachability_graph(&self, basis: &[usize]) -> Graph;
 
-    /// Efficiently find the arm with the maximum gap.
-    fn fast_maxgap(&self, weights: &[f64]) -> usize {
+    /// Get the width of Chen et al., the number of arms to exchange at once
+    /// to move from a superarm to a better one.
+    fn width(&self) -> usize;
+
+    /// Get the underlying graph whose i-th edge is the arm `get_arms()[i]`, if any.
+    fn underlying_graph(&self) -> Option<&Graph> {
+        None
+    }
+
+    /// Efficiently find the arm with the maximum gap, where the smaller index breaks ties.
+    /// Return an error if the weight of a remaining arm is NaN, or if there is no basis.
+    fn fast_maxgap(&self, weights: &[f64]) -> Result<usize, Error> {
+        maxgap_arm(&self.get_arms(), &self.all_gaps(weights)?)
+    }
+
+    /// Efficiently find the gaps indexed by the arms with the reachability graph of the optimal basis.
+    /// The gaps of the removed arms are NaN,
+    /// and those of the arms which every basis or no basis contains are infinite.
+    /// Return an error if the weight of a remaining arm is NaN, or if there is no basis.
+    fn all_gaps(&self, weights: &[f64]) -> Result<Vec<f64>, Error> {
         let arm_num = self.get_arm_num();
+        if weights.len() != arm_num {
+            return Err(Error::ArmNumMismatch {
+                expected: arm_num,
+                actual: weights.len(),
+            });
+        }
+        check_weights(&self.get_arms(), weights)?;
 
         // Find the optimal basis.
-        let opt_basis = self.optimal(weights).unwrap();
+        let opt_basis = self.optimal(weights).ok_or(Error::NoBasis)?;
 
         // Whether or not an arm is in the optimal basis.
         let mut in_opt = vec![false; arm_num];
@@ -67,7 +128,8 @@ pub trait CombinatorialStructure: Clone {
             let mut max_weights = vec![f64::NEG_INFINITY; rvnum];
             for v in 0..rvnum {
                 if indegrees[v] == 0 {
-                    if v < arm_num {
+                    // An arm in the basis which is in no fundamental circuit, such as a bridge, has the infinite gap.
+                    if v < arm_num && !in_opt[v] {
                         max_weights[v] = weights[v];
                     }
                     queue.push_back(v);
@@ -84,9 +146,11 @@ pub trait CombinatorialStructure: Clone {
                 }
             }
 
-            assert!(indegrees.iter().all(|&d| d == 0), "BFS error (to basis)");
+            if indegrees.iter().any(|&d| d != 0) {
+                return Err(Error::CyclicReachabilityGraph);
+            }
 
-            for i in 0..arm_num {
+            for i in self.get_arms() {
                 if in_opt[i] {
                     gaps[i] = weights[i] - max_weights[i];
                 }
@@ -102,7 +166,8 @@ pub trait CombinatorialStructure: Clone {
             let mut min_weights = vec![f64::INFINITY; rvnum];
             for v in 0..rvnum {
                 if indegrees[v] == 0 {
-                    if v < arm_num {
+                    // An arm whose fundamental circuit is itself, such as a self-loop, has the infinite gap.
+                    if v < arm_num && in_opt[v] {
                         min_weights[v] = weights[v];
                     }
                     queue.push_back(v);
@@ -119,31 +184,105 @@ pub trait CombinatorialStructure: Clone {
                 }
             }
 
-            assert!(indegrees.iter().all(|&d| d == 0), "BFS error (from basis)");
+            if indegrees.iter().any(|&d| d != 0) {
+                return Err(Error::CyclicReachabilityGraph);
+            }
 
-            for i in 0..arm_num {
+            for i in self.get_arms() {
                 if !in_opt[i] {
                     gaps[i] = min_weights[i] - weights[i];
                 }
             }
         }
 
-        // Return the index with the maximum gap.
-        let mut maxgap = f64::NEG_INFINITY;
-        let mut maxgap_arm = 0;
-
-        for i in self.get_arms() {
-            if gaps[i] > maxgap {
-                maxgap = gaps[i];
-                maxgap_arm = i;
-            }
-        }
-
-        maxgap_arm
+        Ok(gaps)
     }
 }
 
 pub trait RandomSample {
-    /// Randomly sample an instance with `arm_num` arms.
-    fn sample(arm_num: usize) -> Self;
+    /// Randomly sample an instance with `arm_num` arms by `rng`.
+    fn sample(arm_num: usize, rng: &mut impl Rng) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        circuit_matroid::CircuitMatroid, uniform_matroid::UniformMatroid, CombinatorialStructure,
+    };
+    use crate::{algorithm::naive_maxgap, error::Error, util::graph::generator};
+
+    #[test]
+    fn tie_breaking_test() {
+        // The removed arms shuffle the order of the remaining arms.
+        let mut structure = UniformMatroid::new(8, 4);
+        structure.delete_arm(1).unwrap().contract_arm(2).unwrap();
+        let weights = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.5];
+        let mut optimal = structure.optimal(&weights).unwrap();
+        optimal.sort_unstable();
+        assert_eq!(optimal, vec![0, 3, 4]);
+
+        // Every spanning tree of the complete graph is optimal with the equal weights.
+        let mut structure = CircuitMatroid::new(&generator::complete(5));
+        structure.delete_arm(0).unwrap().delete_arm(4).unwrap();
+        let weights = [1.0; 10];
+        let mut optimal = structure.optimal(&weights).unwrap();
+        optimal.sort_unstable();
+        assert_eq!(optimal, vec![1, 2, 3, 5]);
+
+        // Every arm has the gap 0.
+        assert_eq!(structure.fast_maxgap(&weights), Ok(1));
+        assert_eq!(naive_maxgap(&structure, &weights), Ok(1));
+
+        // The signed zeros are equal.
+        let structure = UniformMatroid::new(2, 1);
+        assert_eq!(structure.optimal(&[0.0, -0.0]), Some(vec![0]));
+        assert_eq!(structure.optimal(&[-0.0, 0.0]), Some(vec![0]));
+        assert_eq!(structure.fast_maxgap(&[-0.0, 0.0]), Ok(0));
+    }
+
+    #[test]
+    fn nan_test() {
+        let structure = UniformMatroid::new(4, 2);
+        let weights = [0.0, 1.0, f64::NAN, 2.0];
+        assert_eq!(
+            structure.fast_maxgap(&weights),
+            Err(Error::NanWeight { arm: 2 })
+        );
+        assert_eq!(
+            naive_maxgap(&structure, &weights),
+            Err(Error::NanWeight { arm: 2 })
+        );
+
+        // The weights of the removed arms are ignored.
+        let mut structure = structure;
+        structure.delete_arm(2).unwrap();
+        assert_eq!(structure.fast_maxgap(&weights), Ok(3));
+    }
+
+    #[test]
+    fn error_test() {
+        let mut structure = UniformMatroid::new(4, 2);
+        structure.delete_arm(1).unwrap();
+        assert_eq!(
+            structure.contract_arm(1).err(),
+            Some(Error::ArmNotFound { arm: 1 })
+        );
+        assert_eq!(
+            structure.delete_arm(4).err(),
+            Some(Error::ArmNotFound { arm: 4 })
+        );
+        assert_eq!(
+            structure.fast_maxgap(&[0.0; 3]),
+            Err(Error::ArmNumMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+
+        // A bridge of the path is deleted.
+        let mut structure = CircuitMatroid::new(&generator::grid(1, 3));
+        structure.delete_arm(0).unwrap();
+        assert_eq!(structure.fast_maxgap(&[0.0; 2]), Err(Error::NoBasis));
+        assert_eq!(naive_maxgap(&structure, &[0.0; 2]), Err(Error::NoBasis));
+    }
 }
@@ -1,13 +1,26 @@
-use super::{CombinatorialStructure, RandomSample};
-use crate::{arms::Weights, util::graph::Graph};
+use super::{compare_arms, find_arm, CombinatorialStructure, RandomSample};
+use crate::{
+    error::Error,
+    util::graph::{generator, Graph},
+};
 use rand::Rng;
-use std::{cmp::max, collections::VecDeque, mem::swap};
-
+use std::{
+    cmp::{max, min},
+    collections::VecDeque,
+    mem::swap,
+};
+
+/// The graphic matroid of a graph, whose bases are the spanning forests.
+/// The graph may be disconnected, and may have parallel edges and self-loops.
 #[derive(Clone)]
 pub struct CircuitMatroid {
     arm_num: usize,
     arms: Vec<usize>,
     graph: Graph,
+    /// The number of edges in a basis, which is kept through deletions.
+    rank: usize,
+    /// Whether a basis exists, which is false after a self-loop is contracted.
+    feasible: bool,
 }
 
 impl CircuitMatroid {
@@ -17,8 +30,31 @@ impl CircuitMatroid {
             arm_num: m,
             arms: (0..m).collect(),
             graph: graph.clone(),
+            rank: graph.get_vnum() - graph.component_num(),
+            feasible: true,
+        }
+    }
+
+    /// Randomly sample a connected simple graph with `vnum` vertices and `arm_num` edges.
+    /// It is required that `vnum - 1 <= arm_num <= vnum * (vnum - 1) / 2`.
+    pub fn sample_with_vnum(arm_num: usize, vnum: usize, rng: &mut impl Rng) -> Self {
+        CircuitMatroid::new(&generator::gnm(vnum, arm_num, rng))
+    }
+
+    /// Randomly choose the number of vertices of a graph with `arm_num` edges.
+    pub fn sample_vnum(arm_num: usize, rng: &mut impl Rng) -> usize {
+        if arm_num >= 40 {
+            rng.gen_range((arm_num / 4)..(arm_num / 3))
+        } else {
+            // A spanning tree needs `vnum - 1` edges.
+            min(max(10, arm_num.saturating_sub(10)), arm_num + 1)
         }
     }
+
+    /// Get the number of vertices of the underlying graph.
+    pub fn get_vnum(&self) -> usize {
+        self.graph.get_vnum()
+    }
 }
 
 impl CombinatorialStructure for CircuitMatroid {
@@ -30,33 +66,50 @@ impl CombinatorialStructure for CircuitMatroid {
         self.arms.clone()
     }
 
-    fn contract_arm(&mut self, i: usize) -> &mut Self {
-        let pos = self.get_arms().iter().position(|&r| r == i).unwrap();
+    fn contract_arm(&mut self, i: usize) -> Result<&mut Self, Error> {
+        let pos = find_arm(&self.arms, i)?;
 
-        self.graph.contract_edge(pos);
+        let (u, v) = self.graph.get_edges()[pos];
+        if u == v {
+            // No basis contains a self-loop.
+            self.feasible = false;
+            self.graph.delete_edge(pos);
+        } else {
+            self.graph.contract_edge(pos);
+            self.rank -= 1;
+        }
         // Keep the order of edges
         self.arms.swap_remove(pos);
 
-        self
+        Ok(self)
     }
 
-    fn delete_arm(&mut self, i: usize) -> &mut Self {
-        let pos = self.get_arms().iter().position(|&r| r == i).unwrap();
+    fn delete_arm(&mut self, i: usize) -> Result<&mut Self, Error> {
+        let pos = find_arm(&self.arms, i)?;
 
         self.graph.delete_edge(pos);
         // Keep the order of edges
         self.arms.swap_remove(pos);
 
-        self
+        Ok(self)
     }
 
     fn optimal(&self, weights: &[f64]) -> Option<Vec<usize>> {
-        // Reorder weights to be edge-indexed.
-        let mapped_weights: Weights = self.get_arms().iter().map(|&i| weights[i]).collect();
+        if !self.feasible {
+            return None;
+        }
+
+        // Sort edges by the weights of their arms in decreasing order.
+        let mut order: Vec<usize> = (0..self.arms.len()).collect();
+        order.sort_unstable_by(|&k, &l| compare_arms(weights, self.arms[k], self.arms[l]));
 
-        self.graph
-            .maximum_spanning_tree(&mapped_weights)
-            .map(|mst| mst.iter().map(|&i| self.arms[i]).collect())
+        // The maximum spanning forest by the Kruskal's algorithm.
+        let forest = self.graph.spanning_forest(&order);
+        // A bridge has been deleted if the forest is smaller than a basis.
+        if forest.len() < self.rank {
+            return None;
+        }
+        Some(forest.iter().map(|&i| self.arms[i]).collect())
     }
 
     fn reachability_graph(&self, opt_arms: &[usize]) -> Graph {
@@ -84,7 +137,7 @@ impl CombinatorialStructure for CircuitMatroid {
 
         let mut result_graph = Graph::new(arm_num);
 
-        // Make rooted from the vertex 0.
+        // Make each tree of the forest rooted.
         // The depth of each vertex.
         let mut depth = vec![0_usize; vnum];
         // Store the parent and the edge to the parent.
@@ -95,43 +148,47 @@ impl CombinatorialStructure for CircuitMatroid {
         let mut queue = VecDeque::<usize>::new();
         let mut visited = vec![false; vnum];
 
-        // Start from the vertex 0.
-        queue.push_back(0);
-        depth[0] = 0;
+        // Start from the smallest vertex of each component.
+        for root in 0..vnum {
+            if visited[root] {
+                continue;
+            }
+            queue.push_back(root);
 
-        while let Some(u) = queue.pop_front() {
-            visited[u] = true;
+            while let Some(u) = queue.pop_front() {
+                visited[u] = true;
 
-            // Doubling.
-            let mut k = 0;
-            while 1 << (k + 1) <= depth[u] {
-                let v = par_vertex[u][k];
-                let uv = par_edge[u][k];
+                // Doubling.
+                let mut k = 0;
+                while 1 << (k + 1) <= depth[u] {
+                    let v = par_vertex[u][k];
+                    let uv = par_edge[u][k];
 
-                let w = par_vertex[v][k];
-                let vw = par_edge[v][k];
-                // Compress the path u -> v -> w.
+                    let w = par_vertex[v][k];
+                    let vw = par_edge[v][k];
+                    // Compress the path u -> v -> w.
 
-                // Create new vertex corresponding to the path uw.
-                let uw = result_graph.get_vnum();
-                result_graph.add_edge(uv, uw).add_edge(vw, uw);
+                    // Create new vertex corresponding to the path uw.
+                    let uw = result_graph.get_vnum();
+                    result_graph.add_edge(uv, uw).add_edge(vw, uw);
 
-                par_vertex[u].push(w);
-                par_edge[u].push(uw);
-                k += 1;
-            }
+                    par_vertex[u].push(w);
+                    par_edge[u].push(uw);
+                    k += 1;
+                }
 
-            for &i in &adj[u] {
-                let (p, q) = graph.get_edges()[i];
-                // The other vertex.
-                let v = u ^ p ^ q;
-
-                if !visited[v] {
-                    // Set p as the parent of q.
-                    depth[v] = depth[u] + 1;
-                    par_vertex[v].push(u);
-                    par_edge[v].push(arms[i]);
-                    queue.push_back(v);
+                for &i in &adj[u] {
+                    let (p, q) = edges[i];
+                    // The other vertex.
+                    let v = u ^ p ^ q;
+
+                    if !visited[v] {
+                        // Set p as the parent of q.
+                        depth[v] = depth[u] + 1;
+                        par_vertex[v].push(u);
+                        par_edge[v].push(arms[i]);
+                        queue.push_back(v);
+                    }
                 }
             }
         }
@@ -196,72 +253,125 @@ impl CombinatorialStructure for CircuitMatroid {
 
         result_graph
     }
-}
-
-impl RandomSample for CircuitMatroid {
-    fn sample(arm_num: usize) -> Self {
-        let mut rng = rand::thread_rng();
 
-        let vnum = if arm_num >= 40 {
-            rng.gen_range((arm_num / 4)..(arm_num / 3))
-        } else {
-            max(10, arm_num - 10)
-        };
-
-        let mut graph = Graph::new(vnum);
-
-        // Build a spanning tree.
-        {
-            let mut us: Vec<usize> = vec![0];
-            let mut vs: Vec<usize> = (1..vnum).collect();
-            for _ in 0..(vnum - 1) {
-                let ui = rng.gen_range(0..us.len());
-                let vi = rng.gen_range(0..vs.len());
-
-                let mut u = us[ui];
-                let mut v = vs[vi];
-                if u > v {
-                    swap(&mut u, &mut v);
-                }
-                graph.add_edge(u, v);
-
-                us.push(vs[vi]);
-                vs.swap_remove(vi);
-            }
-        }
-
-        // Add edges randomly.
-        while graph.get_edges().len() < arm_num {
-            let mut u = rng.gen_range(0..vnum);
-            let mut v = rng.gen_range(0..vnum);
+    /// A spanning forest is exchanged one edge for another, so the width is 2.
+    fn width(&self) -> usize {
+        2
+    }
 
-            if u > v {
-                swap(&mut u, &mut v);
-            }
-            if u != v && !graph.get_edges().contains(&(u, v)) {
-                graph.add_edge(u, v);
-            }
-        }
+    fn underlying_graph(&self) -> Option<&Graph> {
+        Some(&self.graph)
+    }
+}
 
-        CircuitMatroid::new(&graph)
+impl RandomSample for CircuitMatroid {
+    fn sample(arm_num: usize, rng: &mut impl Rng) -> Self {
+        let vnum = CircuitMatroid::sample_vnum(arm_num, rng);
+        CircuitMatroid::sample_with_vnum(arm_num, vnum, rng)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        arms::Weights,
+        algorithm::{csar, naive_gaps, naive_maxgap},
+        arms::{Arm, Arms, Weights},
         structure::{circuit_matroid::CircuitMatroid, CombinatorialStructure, RandomSample},
+        util::graph::{generator, Graph},
     };
-    use rand::Rng;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use std::collections::VecDeque;
 
+    /// Check that `fast_maxgap` agrees with `naive_maxgap` on `graph` for random weights.
+    fn check_maxgap(graph: &Graph, rng: &mut StdRng) {
+        let structure = CircuitMatroid::new(graph);
+        let arm_num = structure.get_arm_num();
+        for _ in 0..20 {
+            let weights: Weights = (0..arm_num).map(|_| rng.gen()).collect();
+            assert_eq!(
+                naive_maxgap(&structure, &weights),
+                structure.fast_maxgap(&weights)
+            );
+        }
+    }
+
+    /// Append the edges of `other` to `graph` with the vertices shifted by `offset`.
+    fn append(graph: &mut Graph, other: &Graph, offset: usize) {
+        for (u, v) in other.get_edges() {
+            graph.add_edge(u + offset, v + offset);
+        }
+    }
+
+    #[test]
+    fn disconnected_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // Three components and an isolated vertex.
+        let mut graph = Graph::new(24);
+        append(&mut graph, &generator::gnm(10, 20, &mut rng), 0);
+        append(&mut graph, &generator::gnm(5, 4, &mut rng), 10);
+        append(&mut graph, &generator::gnm(8, 15, &mut rng), 15);
+        assert_eq!(graph.component_num(), 4);
+
+        let structure = CircuitMatroid::new(&graph);
+        let weights: Weights = (0..39).map(|_| rng.gen()).collect();
+        // The spanning forest.
+        assert_eq!(structure.optimal(&weights).unwrap().len(), 24 - 4);
+
+        check_maxgap(&graph, &mut rng);
+    }
+
+    #[test]
+    fn multigraph_test() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let mut graph = generator::gnm(10, 20, &mut rng);
+        let edges = graph.get_edges();
+        for _ in 0..10 {
+            let (u, v) = edges[rng.gen_range(0..edges.len())];
+            graph.add_edge(v, u);
+        }
+
+        check_maxgap(&graph, &mut rng);
+    }
+
+    #[test]
+    fn self_loop_test() {
+        let mut rng = StdRng::seed_from_u64(2);
+
+        let mut graph = generator::gnm(10, 20, &mut rng);
+        graph.add_edge(3, 3).add_edge(7, 7);
+        let structure = CircuitMatroid::new(&graph);
+
+        // The loops are the heaviest, but never in the optimal basis.
+        let mut weights: Weights = (0..22).map(|_| rng.gen()).collect();
+        weights[20] = 10.0;
+        weights[21] = 10.0;
+        let optimal = structure.optimal(&weights).unwrap();
+        assert_eq!(optimal.len(), 9);
+        assert!(!optimal.contains(&20) && !optimal.contains(&21));
+
+        // The loops are always rejected.
+        let gaps = naive_gaps(&structure, &weights).unwrap();
+        assert_eq!(gaps[20], f64::INFINITY);
+        assert_eq!(gaps[21], f64::INFINITY);
+
+        let mut arms = Arms::new(rng.gen());
+        for &weight in &weights {
+            arms.push_arm(Arm::gaussian(weight, 0.1).unwrap());
+        }
+        let accepted = csar(structure, &mut arms).unwrap();
+        assert!(!accepted.contains(&20) && !accepted.contains(&21));
+
+        check_maxgap(&graph, &mut rng);
+    }
+
     #[test]
     fn reachability_test() {
         let arm_num = 1000;
-        let structure = CircuitMatroid::sample(arm_num);
+        let mut rng = StdRng::seed_from_u64(0);
+        let structure = CircuitMatroid::sample(arm_num, &mut rng);
 
-        let mut rng = rand::thread_rng();
         let weights: Weights = (0..arm_num).map(|_| rng.gen()).collect();
         let opt_arms = structure.optimal(&weights).unwrap();
 
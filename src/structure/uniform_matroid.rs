@@ -1,6 +1,7 @@
 use rand::Rng;
 
-use super::{CombinatorialStructure, RandomSample};
+use super::{compare_arms, find_arm, CombinatorialStructure, RandomSample};
+use crate::error::Error;
 use crate::util::graph::Graph;
 
 #[derive(Clone)]
@@ -8,6 +9,8 @@ pub struct UniformMatroid {
     arm_num: usize,
     indices: Vec<usize>,
     rank: usize,
+    /// Whether a basis exists, which is false after more arms than the rank are contracted.
+    feasible: bool,
 }
 
 impl UniformMatroid {
@@ -16,8 +19,13 @@ impl UniformMatroid {
             arm_num,
             indices: (0..arm_num).collect(),
             rank,
+            feasible: true,
         }
     }
+
+    pub fn get_rank(&self) -> usize {
+        self.rank
+    }
 }
 
 impl CombinatorialStructure for UniformMatroid {
@@ -29,36 +37,33 @@ impl CombinatorialStructure for UniformMatroid {
         self.indices.clone()
     }
 
-    fn contract_arm(&mut self, i: usize) -> &mut Self {
-        let pos = self.get_arms().iter().position(|&r| r == i).unwrap();
+    fn contract_arm(&mut self, i: usize) -> Result<&mut Self, Error> {
+        let pos = find_arm(&self.indices, i)?;
         self.indices.swap_remove(pos);
-        self.rank -= 1;
-        self
+        // No basis contains the arm if the rank is already 0.
+        self.feasible &= self.rank > 0;
+        self.rank = self.rank.saturating_sub(1);
+        Ok(self)
     }
 
-    fn delete_arm(&mut self, i: usize) -> &mut Self {
-        let pos = self.get_arms().iter().position(|&r| r == i).unwrap();
+    fn delete_arm(&mut self, i: usize) -> Result<&mut Self, Error> {
+        let pos = find_arm(&self.indices, i)?;
         self.indices.swap_remove(pos);
-        self
+        Ok(self)
     }
 
     fn optimal(&self, weights: &[f64]) -> Option<Vec<usize>> {
-        if self.indices.len() < self.rank {
+        if !self.feasible || self.indices.len() < self.rank {
             return None;
         }
 
-        // zip indices of arms and their weights
-        let mut indexed_weights: Vec<(usize, f64)> =
-            self.get_arms().iter().map(|&i| (i, weights[i])).collect();
-
         // sort by weights in decreasing order
-        indexed_weights.sort_unstable_by(|(_, fl), (_, fr)| fl.partial_cmp(fr).unwrap().reverse());
+        let mut arms = self.get_arms();
+        arms.sort_unstable_by(|&i, &j| compare_arms(weights, i, j));
 
         // leave first rank elements
-        indexed_weights.truncate(self.rank);
-
-        // map to their original indices
-        Some(indexed_weights.iter().map(|&(i, _)| i).collect())
+        arms.truncate(self.rank);
+        Some(arms)
     }
 
     fn reachability_graph(&self, basis: &[usize]) -> Graph {
@@ -79,11 +84,15 @@ impl CombinatorialStructure for UniformMatroid {
 
         result_graph
     }
+
+    /// A basis is exchanged one arm for another, so the width is 2.
+    fn width(&self) -> usize {
+        2
+    }
 }
 
 impl RandomSample for UniformMatroid {
-    fn sample(arm_num: usize) -> Self {
-        let mut rng = rand::thread_rng();
+    fn sample(arm_num: usize, rng: &mut impl Rng) -> Self {
         let rank = rng.gen_range(0..(arm_num + 1));
         UniformMatroid::new(arm_num, rank)
     }
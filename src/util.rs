@@ -1,2 +1,2 @@
-pub mod union_find;
 pub mod graph;
+pub mod union_find;
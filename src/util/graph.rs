@@ -1,3 +1,7 @@
+pub mod dot;
+pub mod generator;
+pub mod reader;
+
 use crate::util::union_find::UnionFind;
 use std::{cmp::max, mem::swap};
 
@@ -71,35 +75,29 @@ impl Graph {
         self
     }
 
-    /// Find the maximum spanning tree by the Kruskal's algorithm
-    /// It is required that the length of `weights` equals the number of edges.
-    pub fn maximum_spanning_tree(&self, weights: &[f64]) -> Option<Vec<usize>> {
-        // Check the requirement for the length of `weights`.
-        assert_eq!(self.edges.len(), weights.len());
-
-        if self.vnum == 0 {
-            return None;
+    /// Count the connected components.
+    pub fn component_num(&self) -> usize {
+        let mut uf = UnionFind::new(self.vnum);
+        for &(u, v) in &self.edges {
+            uf.unite(u, v);
         }
+        (0..self.vnum).filter(|&v| uf.find_root(v) == v).count()
+    }
 
-        // Sort arms by weights in decreasing order
-        let mut arms: Vec<usize> = (0..self.edges.len()).collect();
-        arms.sort_unstable_by(|&i, &j| weights[i].partial_cmp(&weights[j]).unwrap().reverse());
-
-        // Add the heaviest edge greedily if it doesn't induce any cycle.
-        let mut mst = Vec::<usize>::new();
+    /// Find the spanning forest by adding the edges in `order` greedily if they don't induce any cycle.
+    /// It is the maximum spanning forest by the Kruskal's algorithm if the edges are sorted by weight in decreasing order.
+    /// It spans every connected component, and never contains self-loops.
+    pub fn spanning_forest(&self, order: &[usize]) -> Vec<usize> {
+        let mut forest = Vec::<usize>::new();
         let mut uf = UnionFind::new(self.vnum);
-        for i in arms {
+        for &i in order {
             let (u, v) = self.edges[i];
             if !uf.same(u, v) {
                 uf.unite(u, v);
-                mst.push(i);
+                forest.push(i);
             }
         }
 
-        if uf.get_size(0) == self.vnum {
-            Some(mst)
-        } else {
-            None
-        }
+        forest
     }
 }
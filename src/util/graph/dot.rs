@@ -0,0 +1,132 @@
+use super::Graph;
+use std::fmt::Write;
+
+/// Which elements of a graph are arms.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum DotArms {
+    /// No element is labeled as an arm.
+    #[default]
+    None,
+    /// The i-th edge is the arm `arms[i]`, as in the graph of a circuit matroid.
+    Edges(Vec<usize>),
+    /// The vertices less than the number of arms are the arms,
+    /// and the rest are auxiliary, as in the reachability graphs.
+    Vertices(usize),
+}
+
+/// How a graph is drawn in the Graphviz DOT language.
+#[derive(Clone, Default)]
+pub struct DotOptions {
+    /// Whether the edges are directed.
+    pub directed: bool,
+    pub arms: DotArms,
+    /// The arms drawn in bold, such as the optimal basis.
+    pub highlighted: Vec<usize>,
+    /// The gaps indexed by the arms, shown in the labels of the arms.
+    pub gaps: Option<Vec<f64>>,
+}
+
+impl DotOptions {
+    /// The label of the arm `arm`.
+    fn arm_label(&self, arm: usize) -> String {
+        match self.gaps.as_ref().and_then(|gaps| gaps.get(arm)) {
+            Some(gap) => format!("a{}\\nΔ={:.4}", arm, gap),
+            None => format!("a{}", arm),
+        }
+    }
+
+    fn arm_attributes(&self, arm: usize) -> String {
+        let mut attributes = format!("label=\"{}\"", self.arm_label(arm));
+        if self.highlighted.contains(&arm) {
+            attributes += ", color=red, penwidth=2";
+        }
+        attributes
+    }
+}
+
+impl Graph {
+    /// Write this graph in the Graphviz DOT language.
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let (keyword, edge_op) = if options.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        // Writing into a `String` never fails.
+        let mut dot = String::new();
+        writeln!(dot, "{} G {{", keyword).unwrap();
+        writeln!(dot, "    node [shape=circle];").unwrap();
+
+        for v in 0..self.vnum {
+            match options.arms {
+                DotArms::Vertices(arm_num) if v < arm_num => {
+                    writeln!(dot, "    {} [{}];", v, options.arm_attributes(v)).unwrap()
+                }
+                DotArms::Vertices(_) => writeln!(
+                    dot,
+                    "    {} [label=\"x{}\", shape=box, style=dashed];",
+                    v, v
+                )
+                .unwrap(),
+                DotArms::None | DotArms::Edges(_) => writeln!(dot, "    {};", v).unwrap(),
+            }
+        }
+
+        for (i, &(u, v)) in self.edges.iter().enumerate() {
+            match &options.arms {
+                DotArms::Edges(arms) => writeln!(
+                    dot,
+                    "    {} {} {} [{}];",
+                    u,
+                    edge_op,
+                    v,
+                    options.arm_attributes(arms[i])
+                )
+                .unwrap(),
+                DotArms::None | DotArms::Vertices(_) => {
+                    writeln!(dot, "    {} {} {};", u, edge_op, v).unwrap()
+                }
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DotArms, DotOptions};
+    use crate::util::graph::Graph;
+
+    #[test]
+    fn dot_test() {
+        let mut graph = Graph::new(3);
+        graph.add_edge(0, 1).add_edge(1, 2).add_edge(0, 2);
+
+        let dot = graph.to_dot(&DotOptions::default());
+        assert!(dot.starts_with("graph G {"));
+        assert!(dot.contains("    1 -- 2;"));
+
+        let dot = graph.to_dot(&DotOptions {
+            arms: DotArms::Edges(vec![2, 0, 1]),
+            highlighted: vec![2],
+            gaps: Some(vec![0.5, 0.25, 0.125]),
+            ..DotOptions::default()
+        });
+        assert!(dot.contains("0 -- 1 [label=\"a2\\nΔ=0.1250\", color=red, penwidth=2];"));
+        assert!(dot.contains("1 -- 2 [label=\"a0\\nΔ=0.5000\"];"));
+
+        let dot = graph.to_dot(&DotOptions {
+            directed: true,
+            arms: DotArms::Vertices(2),
+            ..DotOptions::default()
+        });
+        assert!(dot.starts_with("digraph G {"));
+        assert!(dot.contains("    1 [label=\"a1\"];"));
+        assert!(dot.contains("    2 [label=\"x2\", shape=box, style=dashed];"));
+        assert!(dot.contains("    0 -> 2;"));
+    }
+}
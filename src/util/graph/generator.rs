@@ -0,0 +1,293 @@
+use super::Graph;
+use crate::util::union_find::UnionFind;
+use rand::Rng;
+use std::{collections::HashSet, f64::consts::PI, mem::swap};
+
+/// Build a random spanning tree on `vnum` vertices, attaching each new vertex to a random one in the tree.
+/// Return the graph and the set of its edges.
+fn random_tree(vnum: usize, rng: &mut impl Rng) -> (Graph, HashSet<(usize, usize)>) {
+    let mut graph = Graph::new(vnum);
+    let mut edge_set = HashSet::new();
+
+    let mut us: Vec<usize> = vec![0];
+    let mut vs: Vec<usize> = (1..vnum).collect();
+    for _ in 0..vnum.saturating_sub(1) {
+        let ui = rng.gen_range(0..us.len());
+        let vi = rng.gen_range(0..vs.len());
+
+        let mut u = us[ui];
+        let mut v = vs[vi];
+        if u > v {
+            swap(&mut u, &mut v);
+        }
+        graph.add_edge(u, v);
+        edge_set.insert((u, v));
+
+        us.push(vs[vi]);
+        vs.swap_remove(vi);
+    }
+
+    (graph, edge_set)
+}
+
+/// Check that a simple connected graph with `vnum` vertices and `edge_num` edges exists.
+pub fn check_size(vnum: usize, edge_num: usize) -> Result<(), String> {
+    if vnum == 0 || edge_num + 1 < vnum {
+        Err(format!(
+            "{} edges cannot connect {} vertices.",
+            edge_num, vnum
+        ))
+    } else if edge_num > vnum * (vnum - 1) / 2 {
+        Err(format!(
+            "{} vertices have too few pairs for {} edges.",
+            vnum, edge_num
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Randomly sample a connected simple graph with `vnum` vertices and `edge_num` edges,
+/// which is a random spanning tree with uniformly random edges in addition.
+/// It is required that `vnum - 1 <= edge_num <= vnum * (vnum - 1) / 2`.
+pub fn gnm(vnum: usize, edge_num: usize, rng: &mut impl Rng) -> Graph {
+    let (mut graph, mut edge_set) = random_tree(vnum, rng);
+
+    let pair_num = vnum * (vnum - 1) / 2;
+    if edge_num > pair_num / 2 {
+        // Rejection sampling slows down for dense graphs, so draw the edges from the remaining pairs.
+        let mut pairs: Vec<(usize, usize)> = (0..vnum)
+            .flat_map(|u| ((u + 1)..vnum).map(move |v| (u, v)))
+            .filter(|pair| !edge_set.contains(pair))
+            .collect();
+        for k in 0..(edge_num - graph.edges.len()) {
+            let l = rng.gen_range(k..pairs.len());
+            pairs.swap(k, l);
+            graph.add_edge(pairs[k].0, pairs[k].1);
+        }
+        return graph;
+    }
+
+    // Add edges randomly.
+    while edge_set.len() < edge_num {
+        let mut u = rng.gen_range(0..vnum);
+        let mut v = rng.gen_range(0..vnum);
+
+        if u > v {
+            swap(&mut u, &mut v);
+        }
+        if u != v && edge_set.insert((u, v)) {
+            graph.add_edge(u, v);
+        }
+    }
+
+    graph
+}
+
+/// Randomly sample a connected random geometric graph with `vnum` vertices and `edge_num` edges.
+/// The vertices are uniformly random points in the unit square,
+/// and the edges are the Euclidean minimum spanning tree and the shortest pairs in addition.
+/// It is required that `vnum - 1 <= edge_num <= vnum * (vnum - 1) / 2`.
+pub fn random_geometric(vnum: usize, edge_num: usize, rng: &mut impl Rng) -> Graph {
+    let points: Vec<(f64, f64)> = (0..vnum).map(|_| (rng.gen(), rng.gen())).collect();
+    let pair_num = (vnum * vnum.saturating_sub(1) / 2).max(1) as f64;
+
+    // The radius where about twice as many pairs as needed are close,
+    // and which is large enough to connect the points with high probability.
+    let mut radius = (2.0 * edge_num as f64 / pair_num / PI)
+        .max(1.5 * (vnum.max(2) as f64).ln() / vnum as f64 / PI)
+        .sqrt();
+    loop {
+        // Bucket the points into cells of side `radius`, and find the close pairs in the adjacent cells.
+        // At most about `vnum` cells so that they fit in linear space.
+        let max_cell_num = ((vnum as f64).sqrt().ceil() as usize).max(1);
+        let cell_num = ((1.0 / radius).floor() as usize).clamp(1, max_cell_num);
+        let cell_of = |x: f64| ((x * cell_num as f64) as usize).min(cell_num - 1);
+        let mut cells = vec![Vec::<usize>::new(); cell_num * cell_num];
+        for (i, &(x, y)) in points.iter().enumerate() {
+            cells[cell_of(x) * cell_num + cell_of(y)].push(i);
+        }
+
+        let mut pairs = Vec::new();
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let (cx, cy) = (cell_of(x), cell_of(y));
+            for nx in cx.saturating_sub(1)..(cx + 2).min(cell_num) {
+                for ny in cy.saturating_sub(1)..(cy + 2).min(cell_num) {
+                    for &j in &cells[nx * cell_num + ny] {
+                        let (dx, dy) = (x - points[j].0, y - points[j].1);
+                        // Compare the squared distances.
+                        let distance = dx * dx + dy * dy;
+                        if i < j && distance <= radius * radius {
+                            pairs.push((distance, i, j));
+                        }
+                    }
+                }
+            }
+        }
+
+        if pairs.len() >= edge_num {
+            pairs.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+            // The minimum spanning tree among the close pairs first.
+            let mut uf = UnionFind::new(vnum);
+            let mut in_tree = vec![false; pairs.len()];
+            let mut tree_size = 0;
+            for (k, &(_, i, j)) in pairs.iter().enumerate() {
+                if !uf.same(i, j) {
+                    uf.unite(i, j);
+                    in_tree[k] = true;
+                    tree_size += 1;
+                }
+            }
+
+            if uf.get_size(0) == vnum {
+                let mut graph = Graph::new(vnum);
+                let mut rest = edge_num - tree_size;
+                for (k, &(_, i, j)) in pairs.iter().enumerate() {
+                    if in_tree[k] {
+                        graph.add_edge(i, j);
+                    } else if rest > 0 {
+                        graph.add_edge(i, j);
+                        rest -= 1;
+                    }
+                }
+                return graph;
+            }
+        }
+
+        // Too few pairs are close, or they are not connected.
+        radius *= 2_f64.sqrt();
+    }
+}
+
+/// The grid graph with `rows * cols` vertices.
+pub fn grid(rows: usize, cols: usize) -> Graph {
+    let mut graph = Graph::new(rows * cols);
+    for r in 0..rows {
+        for c in 0..cols {
+            let v = r * cols + c;
+            if c + 1 < cols {
+                graph.add_edge(v, v + 1);
+            }
+            if r + 1 < rows {
+                graph.add_edge(v, v + cols);
+            }
+        }
+    }
+    graph
+}
+
+/// The torus graph with `rows * cols` vertices, which is the grid graph with the borders joined.
+/// It is required that `rows >= 3` and `cols >= 3` so that the graph is simple.
+pub fn torus(rows: usize, cols: usize) -> Graph {
+    let mut graph = Graph::new(rows * cols);
+    for r in 0..rows {
+        for c in 0..cols {
+            let v = r * cols + c;
+            let right = r * cols + (c + 1) % cols;
+            let down = ((r + 1) % rows) * cols + c;
+            graph.add_edge(v.min(right), v.max(right));
+            graph.add_edge(v.min(down), v.max(down));
+        }
+    }
+    graph
+}
+
+/// The complete graph with `vnum` vertices.
+pub fn complete(vnum: usize) -> Graph {
+    let mut graph = Graph::new(vnum);
+    for u in 0..vnum {
+        for v in (u + 1)..vnum {
+            graph.add_edge(u, v);
+        }
+    }
+    graph
+}
+
+/// Randomly sample a Barabási–Albert graph with `edge_num` edges.
+/// It starts from the complete graph with `degree + 1` vertices,
+/// and each new vertex is attached to `degree` distinct vertices chosen with probability proportional to their degrees.
+/// The last vertex may have fewer edges to meet `edge_num`.
+/// `degree` is lowered if the initial complete graph has more edges than `edge_num`.
+pub fn barabasi_albert(edge_num: usize, degree: usize, rng: &mut impl Rng) -> Graph {
+    let mut degree = degree.max(1);
+    while degree > 1 && degree * (degree + 1) / 2 > edge_num {
+        degree -= 1;
+    }
+    if edge_num == 0 {
+        return Graph::new(1);
+    }
+
+    let mut graph = complete(degree + 1);
+    // Each vertex appears as many times as its degree.
+    let mut endpoints: Vec<usize> = graph.edges.iter().flat_map(|&(u, v)| [u, v]).collect();
+
+    while graph.edges.len() < edge_num {
+        let v = graph.get_vnum();
+        let new_degree = degree.min(edge_num - graph.edges.len());
+
+        let mut targets = Vec::with_capacity(new_degree);
+        while targets.len() < new_degree {
+            let u = endpoints[rng.gen_range(0..endpoints.len())];
+            if !targets.contains(&u) {
+                targets.push(u);
+            }
+        }
+        for u in targets {
+            graph.add_edge(u, v);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{barabasi_albert, complete, gnm, grid, random_geometric, torus};
+    use crate::util::{graph::Graph, union_find::UnionFind};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::HashSet;
+
+    /// Check that `graph` is simple and connected with `edge_num` edges.
+    fn assert_connected_simple(graph: &Graph, edge_num: usize) {
+        let edges = graph.get_edges();
+        assert_eq!(edges.len(), edge_num);
+
+        let distinct: HashSet<(usize, usize)> =
+            edges.iter().map(|&(u, v)| (u.min(v), u.max(v))).collect();
+        assert_eq!(distinct.len(), edge_num, "Parallel edges.");
+        assert!(edges.iter().all(|&(u, v)| u != v), "A self-loop.");
+
+        let mut uf = UnionFind::new(graph.get_vnum());
+        for &(u, v) in &edges {
+            uf.unite(u, v);
+        }
+        assert_eq!(uf.get_size(0), graph.get_vnum(), "Not connected.");
+    }
+
+    #[test]
+    fn generators_test() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        assert_connected_simple(&gnm(30, 100, &mut rng), 100);
+        assert_connected_simple(&gnm(30, 29, &mut rng), 29);
+        assert_connected_simple(&gnm(10, 45, &mut rng), 45);
+
+        for (vnum, edge_num) in [(1, 0), (2, 1), (50, 49), (50, 200), (20, 190), (1000, 3000)] {
+            let graph = random_geometric(vnum, edge_num, &mut rng);
+            assert_eq!(graph.get_vnum(), vnum);
+            assert_connected_simple(&graph, edge_num);
+        }
+
+        assert_connected_simple(&grid(4, 5), 4 * 4 + 5 * 3);
+        assert_connected_simple(&grid(1, 5), 4);
+        assert_connected_simple(&torus(3, 4), 24);
+        assert_connected_simple(&complete(6), 15);
+
+        for (edge_num, degree) in [(1, 2), (2, 2), (100, 3), (101, 3), (500, 1)] {
+            assert_connected_simple(&barabasi_albert(edge_num, degree, &mut rng), edge_num);
+        }
+    }
+}
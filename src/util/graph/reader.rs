@@ -0,0 +1,455 @@
+use super::Graph;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// The formats of graph files.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum GraphFormat {
+    /// One edge `u v` per line with 0-indexed vertices.
+    EdgeList,
+    /// The DIMACS format with a problem line `p edge <N> <M>` and edge lines `e u v`.
+    Dimacs,
+    /// The METIS format of adjacency lists.
+    Metis,
+    /// The Matrix Market coordinate format of an adjacency matrix.
+    MatrixMarket,
+}
+
+impl GraphFormat {
+    pub const ALL: [GraphFormat; 4] = [
+        GraphFormat::EdgeList,
+        GraphFormat::Dimacs,
+        GraphFormat::Metis,
+        GraphFormat::MatrixMarket,
+    ];
+
+    /// The name used in the command-line arguments.
+    pub fn key(&self) -> &'static str {
+        match self {
+            GraphFormat::EdgeList => "edgelist",
+            GraphFormat::Dimacs => "dimacs",
+            GraphFormat::Metis => "metis",
+            GraphFormat::MatrixMarket => "mtx",
+        }
+    }
+
+    /// Guess the format from the extension of `path`, falling back to the edge list.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("dimacs" | "col" | "gr") => GraphFormat::Dimacs,
+            Some("metis" | "graph") => GraphFormat::Metis,
+            Some("mtx") => GraphFormat::MatrixMarket,
+            _ => GraphFormat::EdgeList,
+        }
+    }
+}
+
+/// The reward distribution of an edge given in a graph file.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EdgeReward {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// The standard deviation of an edge whose mean is given alone.
+const DEFAULT_STD_DEV: f64 = 1.0;
+
+/// A graph loaded from a file.
+#[derive(Clone)]
+pub struct GraphFile {
+    pub graph: Graph,
+    /// The rewards of the edges if the file gives them to every edge.
+    pub rewards: Option<Vec<EdgeReward>>,
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn parse_token<T: std::str::FromStr>(token: &str, line_i: usize) -> io::Result<T> {
+    token
+        .parse()
+        .map_err(|_| invalid_data(format!("Invalid token {:?} at line {}.", token, line_i + 1)))
+}
+
+/// Convert a 1-indexed vertex to 0-indexed one.
+fn parse_vertex(token: &str, vnum: usize, line_i: usize) -> io::Result<usize> {
+    match parse_token::<usize>(token, line_i)? {
+        v if 0 < v && v <= vnum => Ok(v - 1),
+        _ => Err(invalid_data(format!(
+            "The vertex {} at line {} is out of range.",
+            token,
+            line_i + 1
+        ))),
+    }
+}
+
+/// Collect edges and their optional rewards.
+struct GraphBuilder {
+    graph: Graph,
+    rewards: Vec<Option<EdgeReward>>,
+}
+
+impl GraphBuilder {
+    fn new(vnum: usize) -> Self {
+        GraphBuilder {
+            graph: Graph::new(vnum),
+            rewards: Vec::new(),
+        }
+    }
+
+    /// Add an edge with the optional columns of the mean and the standard deviation.
+    fn add_edge(&mut self, u: usize, v: usize, columns: &[&str], line_i: usize) -> io::Result<()> {
+        let reward = match columns {
+            [] => None,
+            [mean] => Some(EdgeReward {
+                mean: parse_token(mean, line_i)?,
+                std_dev: DEFAULT_STD_DEV,
+            }),
+            [mean, std_dev] => Some(EdgeReward {
+                mean: parse_token(mean, line_i)?,
+                std_dev: parse_token(std_dev, line_i)?,
+            }),
+            _ => {
+                return Err(invalid_data(format!(
+                    "Too many columns at line {}.",
+                    line_i + 1
+                )))
+            }
+        };
+        if let Some(EdgeReward { mean, std_dev }) = reward {
+            if !mean.is_finite() || !std_dev.is_finite() || std_dev < 0.0 {
+                return Err(invalid_data(format!(
+                    "Invalid reward at line {}.",
+                    line_i + 1
+                )));
+            }
+        }
+
+        self.graph.add_edge(u.min(v), u.max(v));
+        self.rewards.push(reward);
+        Ok(())
+    }
+
+    fn build(self) -> io::Result<GraphFile> {
+        let GraphBuilder { graph, rewards } = self;
+
+        if graph.get_edges().is_empty() {
+            return Err(invalid_data("The graph has no edge.".to_string()));
+        }
+
+        let rewards = if rewards.iter().all(Option::is_none) {
+            None
+        } else {
+            Some(
+                rewards
+                    .into_iter()
+                    .collect::<Option<Vec<EdgeReward>>>()
+                    .ok_or_else(|| invalid_data("Some edges have no reward.".to_string()))?,
+            )
+        };
+
+        Ok(GraphFile { graph, rewards })
+    }
+}
+
+/// Iterate over the lines with their indices, skipping empty lines and comments.
+fn content_lines<'a>(
+    text: &'a str,
+    comment: &'a [char],
+) -> impl Iterator<Item = (usize, Vec<&'a str>)> + 'a {
+    text.lines()
+        .enumerate()
+        .map(|(line_i, line)| (line_i, line.trim()))
+        .filter(move |(_, line)| !line.is_empty() && !line.starts_with(comment))
+        .map(|(line_i, line)| {
+            let tokens = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|token| !token.is_empty())
+                .collect();
+            (line_i, tokens)
+        })
+}
+
+impl GraphFile {
+    /// Parse a graph file.
+    ///
+    /// Each edge line may be followed by the mean and the standard deviation of the reward of the edge.
+    /// The standard deviation defaults to 1.
+    /// Either every edge or no edge has them.
+    ///
+    /// * Edge lists: `u v [mean [std]]` with 0-indexed vertices.
+    ///   Lines starting with `#` or `%` are comments.
+    /// * DIMACS: `p edge <N> <M>` followed by `e u v [mean [std]]` with 1-indexed vertices.
+    ///   `a` is accepted in place of `e`, and lines starting with `c` are comments.
+    /// * METIS: `<N> <M> [fmt [ncon]]` followed by the adjacency list of each vertex.
+    ///   The edge weights, if any, are the means.
+    /// * Matrix Market: a coordinate matrix, whose off-diagonal entries are the edges.
+    ///   The values, if any, are the means.
+    ///
+    /// The graph may be disconnected, and may have parallel edges and self-loops,
+    /// except for the diagonal entries of Matrix Market, which are ignored.
+    pub fn parse(text: &str, format: GraphFormat) -> io::Result<Self> {
+        match format {
+            GraphFormat::EdgeList => Self::parse_edge_list(text),
+            GraphFormat::Dimacs => Self::parse_dimacs(text),
+            GraphFormat::Metis => Self::parse_metis(text),
+            GraphFormat::MatrixMarket => Self::parse_matrix_market(text),
+        }
+    }
+
+    /// Load a graph file in `format`.
+    pub fn from_file(path: impl AsRef<Path>, format: GraphFormat) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?, format)
+    }
+
+    fn parse_edge_list(text: &str) -> io::Result<Self> {
+        let mut builder = GraphBuilder::new(0);
+        for (line_i, tokens) in content_lines(text, &['#', '%']) {
+            if tokens.len() < 2 {
+                return Err(invalid_data(format!(
+                    "Too few columns at line {}.",
+                    line_i + 1
+                )));
+            }
+            let u = parse_token(tokens[0], line_i)?;
+            let v = parse_token(tokens[1], line_i)?;
+            builder.add_edge(u, v, &tokens[2..], line_i)?;
+        }
+        builder.build()
+    }
+
+    fn parse_dimacs(text: &str) -> io::Result<Self> {
+        let mut builder = None;
+        for (line_i, tokens) in content_lines(text, &['c']) {
+            match (tokens[0], &mut builder) {
+                ("p", None) if tokens.len() >= 3 => {
+                    let vnum = parse_token(tokens[tokens.len() - 2], line_i)?;
+                    builder = Some((vnum, GraphBuilder::new(vnum)));
+                }
+                ("e" | "a", Some((vnum, builder))) if tokens.len() >= 3 => {
+                    let u = parse_vertex(tokens[1], *vnum, line_i)?;
+                    let v = parse_vertex(tokens[2], *vnum, line_i)?;
+                    builder.add_edge(u, v, &tokens[3..], line_i)?;
+                }
+                _ => {
+                    return Err(invalid_data(format!(
+                        "Unexpected line {} in DIMACS.",
+                        line_i + 1
+                    )))
+                }
+            }
+        }
+        builder
+            .ok_or_else(|| invalid_data("No problem line in DIMACS.".to_string()))?
+            .1
+            .build()
+    }
+
+    fn parse_metis(text: &str) -> io::Result<Self> {
+        // The empty lines are the vertices without neighbors.
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim_start().starts_with('%'));
+
+        let (header_i, header) = lines
+            .find(|(_, line)| !line.trim().is_empty())
+            .ok_or_else(|| invalid_data("No header in METIS.".to_string()))?;
+        let header: Vec<&str> = header.split_whitespace().collect();
+        let vnum: usize = parse_token(header[0], header_i)?;
+        // The digits of `fmt` flag the vertex sizes, the vertex weights and the edge weights.
+        let fmt = format!("{:0>3}", header.get(2).copied().unwrap_or("0"));
+        let [has_sizes, has_vertex_weights, has_edge_weights] = match fmt.as_bytes() {
+            &[a, b, c] => [a == b'1', b == b'1', c == b'1'],
+            _ => return Err(invalid_data(format!("Invalid fmt {:?} in METIS.", fmt))),
+        };
+        let ncon = match header.get(3) {
+            Some(ncon) => parse_token(ncon, header_i)?,
+            None => usize::from(has_vertex_weights),
+        };
+
+        let mut builder = GraphBuilder::new(vnum);
+        for u in 0..vnum {
+            let (line_i, line) = lines
+                .next()
+                .ok_or_else(|| invalid_data(format!("The vertex {} is missing.", u + 1)))?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+
+            let skip = usize::from(has_sizes) + if has_vertex_weights { ncon } else { 0 };
+            let neighbors = tokens.get(skip..).unwrap_or_default();
+            let step = if has_edge_weights { 2 } else { 1 };
+            if neighbors.len() % step != 0 {
+                return Err(invalid_data(format!(
+                    "An edge weight is missing at line {}.",
+                    line_i + 1
+                )));
+            }
+
+            for chunk in neighbors.chunks(step) {
+                let v = parse_vertex(chunk[0], vnum, line_i)?;
+                // Each edge appears in the lists of both ends.
+                if u <= v {
+                    builder.add_edge(u, v, &chunk[1..], line_i)?;
+                }
+            }
+        }
+        builder.build()
+    }
+
+    fn parse_matrix_market(text: &str) -> io::Result<Self> {
+        let header = text
+            .lines()
+            .next()
+            .ok_or_else(|| invalid_data("No header in Matrix Market.".to_string()))?
+            .to_lowercase();
+        let header: Vec<&str> = header.split_whitespace().collect();
+        if header.len() < 5 || header[0] != "%%matrixmarket" || header[2] != "coordinate" {
+            return Err(invalid_data(
+                "Only coordinate matrices of Matrix Market are supported.".to_string(),
+            ));
+        }
+        let has_values = header[3] != "pattern";
+        let is_general = header[4] == "general";
+
+        let mut lines = content_lines(text, &['%']);
+        let (size_i, size) = lines
+            .next()
+            .ok_or_else(|| invalid_data("No size line in Matrix Market.".to_string()))?;
+        if size.len() < 2 {
+            return Err(invalid_data(format!(
+                "Invalid size line {} in Matrix Market.",
+                size_i + 1
+            )));
+        }
+        let vnum: usize = parse_token::<usize>(size[0], size_i)?.max(parse_token(size[1], size_i)?);
+
+        let mut builder = GraphBuilder::new(vnum);
+        // A general matrix may hold both (i, j) and (j, i) with the same value,
+        // while a symmetric one holds only one of them.
+        let mut values = HashMap::new();
+        for (line_i, tokens) in lines {
+            if tokens.len() < 2 + usize::from(has_values) {
+                return Err(invalid_data(format!(
+                    "Too few columns at line {}.",
+                    line_i + 1
+                )));
+            }
+            let u = parse_vertex(tokens[0], vnum, line_i)?;
+            let v = parse_vertex(tokens[1], vnum, line_i)?;
+            if u == v {
+                continue;
+            }
+            let value = if has_values {
+                Some(parse_token::<f64>(tokens[2], line_i)?)
+            } else {
+                None
+            };
+            match values.entry((u.min(v), u.max(v))) {
+                Entry::Occupied(entry) => {
+                    if !is_general || *entry.get() != value {
+                        return Err(invalid_data(format!(
+                            "The duplicate entry at line {} conflicts with the earlier one.",
+                            line_i + 1
+                        )));
+                    }
+                    continue;
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+            let columns = if has_values { &tokens[2..3] } else { &[][..] };
+            builder.add_edge(u, v, columns, line_i)?;
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EdgeReward, GraphFile, GraphFormat};
+
+    /// The cycle of length 4 with a chord between 0 and 2.
+    const EDGES: [(usize, usize); 5] = [(0, 1), (1, 2), (2, 3), (0, 3), (0, 2)];
+
+    fn assert_edges(file: &GraphFile) {
+        assert_eq!(file.graph.get_vnum(), 4);
+        let mut edges = file.graph.get_edges();
+        edges.sort_unstable();
+        let mut expected = EDGES.to_vec();
+        expected.sort_unstable();
+        assert_eq!(edges, expected);
+    }
+
+    #[test]
+    fn formats_test() {
+        let edge_list =
+            "# u v mean std\n0 1 0.5 0.1\n1 2 0.4 0.1\n2 3 0.3 0.1\n0 3 0.2 0.1\n0 2 0.1 0.1\n";
+        let file = GraphFile::parse(edge_list, GraphFormat::EdgeList).unwrap();
+        assert_edges(&file);
+        assert_eq!(
+            file.rewards.unwrap()[0],
+            EdgeReward {
+                mean: 0.5,
+                std_dev: 0.1
+            }
+        );
+
+        let dimacs = "c cycle\np edge 4 5\ne 1 2 0.5\ne 2 3 0.4\ne 3 4 0.3\ne 1 4 0.2\ne 1 3 0.1\n";
+        let file = GraphFile::parse(dimacs, GraphFormat::Dimacs).unwrap();
+        assert_edges(&file);
+        let rewards = file.rewards.unwrap();
+        assert_eq!(rewards[4].mean, 0.1);
+        assert_eq!(rewards[4].std_dev, 1.0);
+
+        let metis = "% cycle\n4 5\n2 4 3\n1 3\n2 4 1\n1 3\n";
+        let file = GraphFile::parse(metis, GraphFormat::Metis).unwrap();
+        assert_edges(&file);
+        assert!(file.rewards.is_none());
+
+        let weighted_metis = "4 5 1\n2 5 4 2 3 1\n1 5 3 4\n2 4 4 3 1 1\n1 2 3 3\n";
+        let file = GraphFile::parse(weighted_metis, GraphFormat::Metis).unwrap();
+        assert_edges(&file);
+        let mut means: Vec<f64> = file.rewards.unwrap().iter().map(|r| r.mean).collect();
+        means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(means, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let mtx = "%%MatrixMarket matrix coordinate pattern symmetric\n% cycle\n4 4 5\n2 1\n3 2\n4 3\n4 1\n3 1\n";
+        let file = GraphFile::parse(mtx, GraphFormat::MatrixMarket).unwrap();
+        assert_edges(&file);
+
+        let general = "%%MatrixMarket matrix coordinate real general\n4 4 7\n1 2 0.5\n2 1 0.5\n2 3 1\n3 4 1\n1 4 1\n1 3 1\n1 1 9\n";
+        let file = GraphFile::parse(general, GraphFormat::MatrixMarket).unwrap();
+        assert_edges(&file);
+        assert_eq!(file.rewards.unwrap()[0].mean, 0.5);
+
+        // The mirrored entries must agree, and a symmetric matrix holds only one of them.
+        let mismatched = general.replace("2 1 0.5", "2 1 0.7");
+        assert!(GraphFile::parse(&mismatched, GraphFormat::MatrixMarket).is_err());
+        let duplicated = mtx.replace("3 1\n", "3 1\n1 3\n");
+        assert!(GraphFile::parse(&duplicated, GraphFormat::MatrixMarket).is_err());
+    }
+
+    #[test]
+    fn invalid_test() {
+        // Self-loops, parallel edges and disconnected graphs are accepted.
+        let file = GraphFile::parse("0 0\n0 1\n1 0\n2 3\n", GraphFormat::EdgeList).unwrap();
+        assert_eq!(file.graph.get_edges(), vec![(0, 0), (0, 1), (0, 1), (2, 3)]);
+        assert!(GraphFile::parse("# empty", GraphFormat::EdgeList).is_err());
+        // Some edges have no reward.
+        assert!(GraphFile::parse("0 1 0.5\n1 2\n", GraphFormat::EdgeList).is_err());
+        // Out of range.
+        assert!(GraphFile::parse("p edge 2 1\ne 1 3\n", GraphFormat::Dimacs).is_err());
+        assert!(GraphFile::parse("e 1 2\n", GraphFormat::Dimacs).is_err());
+        assert!(GraphFile::parse("3 2\n2\n1 3\n", GraphFormat::Metis).is_err());
+
+        assert_eq!(GraphFormat::from_path("net.mtx"), GraphFormat::MatrixMarket);
+        assert_eq!(GraphFormat::from_path("net.txt"), GraphFormat::EdgeList);
+    }
+}